@@ -0,0 +1,103 @@
+
+use criterion::{criterion_group, criterion_main, Criterion, BenchmarkId, Throughput};
+use mobec::{EntityList, EntityBase, define_entity};
+
+#[derive(Debug, Clone, Copy)]
+pub struct P;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Speed;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Dead;
+
+define_entity!{
+    #[derive(Debug)]
+    pub struct Entity {
+        props => {
+            pos: P,
+        },
+        components => {
+            speed => Speed,
+            dead => Dead,
+        }
+    }
+}
+
+/// Generates a list where every entity has a speed, and 1 entity out of 3 is dead.
+fn generate_packed_list(list_size: u32) -> EntityList<Entity> {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    for i in 0..list_size {
+        let e = Entity::new((P,))
+            .with(Speed);
+        if i % 3 == 0 {
+            entity_list.insert(e.with(Dead));
+        } else {
+            entity_list.insert(e);
+        }
+    }
+
+    entity_list
+}
+
+/// Generates a list of `list_size` entities, and then removes all of them but 1 out of 1000,
+/// leaving big bitsets for few entities.
+fn generate_sparse_list(list_size: u32) -> EntityList<Entity> {
+    let mut entity_list = generate_packed_list(list_size);
+    let mut i = 0;
+    entity_list.retain(|_id, _e| {
+        i += 1;
+        i % 1000 == 0
+    });
+    entity_list
+}
+
+pub fn clone_packed(c: &mut Criterion) {
+    let mut group = c.benchmark_group("clone_packed_full");
+    for size in [100, 1_000, 10_000, 100_000].iter() {
+        group.throughput(Throughput::Elements(*size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), size, |b, &size| {
+            let list = generate_packed_list(size as u32);
+            b.iter(|| list.clone())
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("clone_packed_entities_only");
+    for size in [100, 1_000, 10_000, 100_000].iter() {
+        group.throughput(Throughput::Elements(*size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), size, |b, &size| {
+            let list = generate_packed_list(size as u32);
+            b.iter(|| list.clone_entities_only())
+        });
+    }
+}
+
+pub fn clone_sparse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("clone_sparse_full");
+    for size in [1_000, 10_000, 100_000, 1_000_000].iter() {
+        group.throughput(Throughput::Elements(*size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), size, |b, &size| {
+            let list = generate_sparse_list(size as u32);
+            b.iter(|| list.clone())
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("clone_sparse_entities_only");
+    for size in [1_000, 10_000, 100_000, 1_000_000].iter() {
+        group.throughput(Throughput::Elements(*size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), size, |b, &size| {
+            let list = generate_sparse_list(size as u32);
+            b.iter(|| list.clone_entities_only())
+        });
+    }
+}
+
+criterion_group!{
+    name = benches;
+    config = Criterion::default().sample_size(30);
+    targets = clone_packed, clone_sparse
+}
+criterion_main!{benches}
\ No newline at end of file
@@ -200,6 +200,17 @@ fn update_dual_component_list(list: &mut EntityList<Entity>) {
     }
 }
 
+fn update_dual_component_list_scan(list: &mut EntityList<Entity>) {
+    for (_i, e) in list.iter_mut_scan::<(Speed, CollisionBox,)>() {
+        let Speed {x: speed_x, y: speed_y } = e.speed.as_ref().unwrap().as_ref();
+        let c: &CollisionBox = e.collision_box.as_ref().unwrap().as_ref();
+        if ! c.is_static {
+            e.pos.x += speed_x;
+            e.pos.y += speed_y;
+        }
+    }
+}
+
 fn maybe_update_dual_component_list(list: &mut EntityList<Entity>) {
     for (_i, e) in list.iter_all_mut() {
         if e.has::<Speed>() && e.has::<CollisionBox>() {
@@ -213,6 +224,30 @@ fn maybe_update_dual_component_list(list: &mut EntityList<Entity>) {
     }
 }
 
+fn generate_property_list(list_size: u32) -> EntityList<Entity> {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    for i in 0..list_size {
+        entity_list.insert(Entity::new((P { x: i as f32, y: -(i as f32) },)));
+    }
+
+    entity_list
+}
+
+fn update_properties(list: &mut EntityList<Entity>) {
+    list.for_each_property_mut(|_i, e| {
+        e.pos.x += 1.0;
+        e.pos.y -= 1.0;
+    });
+}
+
+fn update_properties_query(list: &mut EntityList<Entity>) {
+    for (_i, e) in list.iter_mut::<()>() {
+        e.pos.x += 1.0;
+        e.pos.y -= 1.0;
+    }
+}
+
 pub fn iter_single_component(c: &mut Criterion) {
     let mut group = c.benchmark_group("single_component");
     for size in [100, 1_000, 10_000, 100_000, 1_000_000].iter() {
@@ -279,6 +314,28 @@ pub fn iter_dual_component_packed(c: &mut Criterion) {
     }
 }
 
+pub fn iter_dual_component_scan_sparse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("dual_component_scan_sparse1");
+    for size in [100, 1_000, 10_000, 100_000, 1_000_000].iter() {
+        group.throughput(Throughput::Elements(*size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), size, |b, &size| {
+            let mut list = generate_dual_component_list_quite_sparse(size as u32);
+            b.iter(|| update_dual_component_list_scan(&mut list))
+        });
+    }
+}
+
+pub fn iter_dual_component_scan_packed(c: &mut Criterion) {
+    let mut group = c.benchmark_group("dual_component_scan_packed");
+    for size in [100, 1_000, 10_000, 100_000, 1_000_000].iter() {
+        group.throughput(Throughput::Elements(*size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), size, |b, &size| {
+            let mut list = generate_dual_component_list_packed(size as u32);
+            b.iter(|| update_dual_component_list_scan(&mut list))
+        });
+    }
+}
+
 pub fn iter_all(c: &mut Criterion) {
     let mut group = c.benchmark_group("iter_all");
     for size in [100, 1_000, 10_000, 100_000, 1_000_000].iter() {
@@ -290,9 +347,24 @@ pub fn iter_all(c: &mut Criterion) {
     }
 }
 
+pub fn property_update(c: &mut Criterion) {
+    let mut group = c.benchmark_group("property_update");
+    for size in [100, 1_000, 10_000, 100_000, 1_000_000].iter() {
+        group.throughput(Throughput::Elements(*size as u64));
+        group.bench_with_input(BenchmarkId::new("for_each_property_mut", size), size, |b, &size| {
+            let mut list = generate_property_list(size as u32);
+            b.iter(|| update_properties(&mut list))
+        });
+        group.bench_with_input(BenchmarkId::new("iter_mut_unit", size), size, |b, &size| {
+            let mut list = generate_property_list(size as u32);
+            b.iter(|| update_properties_query(&mut list))
+        });
+    }
+}
+
 criterion_group!{
     name = benches;
     config = Criterion::default().sample_size(30);
-    targets = iter_single_component, iter_dual_component, iter_dual_component_sparse, iter_dual_component_very_sparse, iter_dual_component_grouped, iter_dual_component_packed, iter_all
+    targets = iter_single_component, iter_dual_component, iter_dual_component_sparse, iter_dual_component_very_sparse, iter_dual_component_grouped, iter_dual_component_packed, iter_dual_component_scan_sparse, iter_dual_component_scan_packed, iter_all, property_update
 }
 criterion_main!{benches}
\ No newline at end of file
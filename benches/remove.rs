@@ -0,0 +1,78 @@
+
+use criterion::{criterion_group, criterion_main, Criterion, BenchmarkId, Throughput};
+use mobec::{EntityList, EntityBase, define_entity};
+
+#[derive(Debug, Clone, Copy)]
+pub struct P;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Speed;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Dead;
+
+define_entity!{
+    #[derive(Debug)]
+    pub struct Entity {
+        props => {
+            pos: P,
+        },
+        components => {
+            speed => Speed,
+            dead => Dead,
+        }
+    }
+}
+
+/// Generates a list where 1 entity out of 100 is dead.
+fn generate_sparse_dead_list(list_size: u32) -> EntityList<Entity> {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    for i in 0..list_size {
+        let e = Entity::new((P,))
+            .with(Speed);
+        if i % 100 == 0 {
+            entity_list.insert(e.with(Dead));
+        } else {
+            entity_list.insert(e);
+        }
+    }
+
+    entity_list
+}
+
+fn remove_dead_query(mut list: EntityList<Entity>) -> EntityList<Entity> {
+    list.remove_query::<(Dead,)>();
+    list
+}
+
+fn remove_dead_retain(mut list: EntityList<Entity>) -> EntityList<Entity> {
+    list.retain(|_id, e| !e.has::<Dead>());
+    list
+}
+
+pub fn remove_sparse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("remove_sparse_query");
+    for size in [100, 1_000, 10_000, 100_000, 1_000_000].iter() {
+        group.throughput(Throughput::Elements(*size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), size, |b, &size| {
+            b.iter_batched(|| generate_sparse_dead_list(size as u32), remove_dead_query, criterion::BatchSize::LargeInput)
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("remove_sparse_retain");
+    for size in [100, 1_000, 10_000, 100_000, 1_000_000].iter() {
+        group.throughput(Throughput::Elements(*size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), size, |b, &size| {
+            b.iter_batched(|| generate_sparse_dead_list(size as u32), remove_dead_retain, criterion::BatchSize::LargeInput)
+        });
+    }
+}
+
+criterion_group!{
+    name = benches;
+    config = Criterion::default().sample_size(30);
+    targets = remove_sparse
+}
+criterion_main!{benches}
\ No newline at end of file
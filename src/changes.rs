@@ -0,0 +1,164 @@
+use hibitset::{BitSet, BitSetAnd, BitSetLike};
+
+use crate::{Component, EntityBase, EntityId, EntityList};
+use crate::entity_list::{bitset_index, get_bitset, get_bitset_mut};
+use crate::iter::{MultiComponent, out_of_date_bitset};
+
+/// A logical time step of an `EntityList`, used for change detection.
+///
+/// Ticks only move forward, via `EntityList::advance_tick`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Tick(pub u64);
+
+/// The changes of a tracked component: which entities changed, and at which tick they last did.
+#[derive(Debug, Clone, Default)]
+pub (crate) struct ChangedSet {
+    bitset: BitSet,
+    /// Tick of the last change, by entity index. Only meaningful for the indices in `bitset`.
+    ticks: Vec<Tick>,
+}
+
+impl ChangedSet {
+    pub (crate) fn add(&mut self, index: u32, tick: Tick) {
+        self.bitset.add(index);
+        let index = index as usize;
+        if self.ticks.len() <= index {
+            self.ticks.resize(index + 1, Tick::default());
+        }
+        self.ticks[index] = tick;
+    }
+
+    pub (crate) fn remove(&mut self, index: u32) {
+        self.bitset.remove(index);
+    }
+
+    pub (crate) fn clear(&mut self) {
+        self.bitset.clear();
+        self.ticks.clear();
+    }
+
+    #[inline]
+    pub (crate) fn bitset(&self) -> &BitSet {
+        &self.bitset
+    }
+
+    #[inline]
+    fn changed_since(&self, index: u32, since: Tick) -> bool {
+        self.ticks[index as usize] >= since
+    }
+}
+
+impl<E: EntityBase> EntityList<E> {
+    /// Start tracking changes for the component `C`.
+    ///
+    /// Change tracking is opt-in: components that are not tracked never appear in `iter_changed`,
+    /// and cost nothing. Tracking starts empty, even if some entities already have the component.
+    pub fn track_changes<C: Component<E>>(&mut self) {
+        self.changed.entry(C::INDEX).or_default();
+    }
+
+    /// Mark the component `C` of the given entity as changed.
+    ///
+    /// Changes made via `insert` and `add_component_for_entity` are marked automatically.
+    /// Use this when you mutated a component through a raw reference (`get_mut`, `iter_mut`, ...).
+    ///
+    /// Does nothing if `C` is not tracked or if the entity does not exist anymore.
+    pub fn mark_changed<C: Component<E>>(&mut self, id: EntityId) {
+        if self.entities.contains(id) {
+            self.mark_changed_index(C::INDEX, id.into_raw_parts().0);
+        }
+    }
+
+    /// Iterate over the entities which have the component `C`, and whose `C` has changed during
+    /// the tick `since` or after it.
+    ///
+    /// `advance_tick` forgets every change, so only the changes of the current tick are ever
+    /// yielded: passing `current_tick()` or an older tick yields all of them, a tick in the future
+    /// yields nothing.
+    pub fn iter_changed<'a, C: Component<E>>(&'a self, since: Tick) -> impl Iterator<Item=(EntityId, &'a E)> + 'a {
+        let component_index = C::INDEX;
+        let entities = &self.entities;
+        self.changed.get(&component_index)
+            .and_then(|changed| get_bitset(&self.bitsets, component_index).map(|bitset| (changed, BitSetAnd(changed.bitset(), bitset))))
+            .into_iter()
+            .flat_map(move |(changed, bitset)| {
+                bitset.iter()
+                    .filter(move |index| changed.changed_since(*index, since))
+                    .map(move |index| {
+                        entities.get_unknown_gen(index as usize)
+                            .map(|(e, id)| (id, e))
+                            .unwrap_or_else(|| out_of_date_bitset(<(C,)>::query_name, index))
+                    })
+            })
+    }
+
+    #[inline]
+    /// Returns the current tick of the list.
+    pub fn current_tick(&self) -> Tick {
+        self.tick
+    }
+
+    /// Forget every change of the component `C`, whatever its tick.
+    ///
+    /// Useful if a single system consumes the changes of `C`, while other changes must stay visible
+    /// to other systems.
+    pub fn clear_changed<C: Component<E>>(&mut self) {
+        if let Some(changed) = self.changed.get_mut(&C::INDEX) {
+            changed.clear();
+        }
+    }
+
+    /// Removes the event components (see `define_entity`) from every entity, without moving on to
+    /// the next tick.
+    ///
+    /// The changes of the event components are forgotten as well, since no entity has them anymore.
+    pub fn clear_events(&mut self) {
+        let bitsets = &mut self.bitsets;
+        let changed = &mut self.changed;
+        // the entities with at least one event, so that every entity is cleared only once
+        let mut with_events = BitSet::new();
+        E::for_all_events(|component_index: usize| {
+            if let Some(bitset) = get_bitset_mut(bitsets, component_index) {
+                for index in (&*bitset).iter() {
+                    with_events.add(index);
+                }
+                bitset.clear();
+            }
+            if let Some(changed) = changed.get_mut(&component_index) {
+                changed.clear();
+            }
+        });
+        for index in (&with_events).iter() {
+            if let Some((e, _id)) = self.entities.get_unknown_gen_mut(index as usize) {
+                e.clear_events();
+            }
+        }
+    }
+
+    /// Move on to the next tick, and reset the changes of every tracked component.
+    ///
+    /// `iter_changed` starts empty again, whatever the tick given to it: changes must be read
+    /// before calling this, which is typically done once at every frame boundary.
+    ///
+    /// Event components are removed first, so an event added during a tick can be seen until the
+    /// end of this tick only.
+    pub fn advance_tick(&mut self) {
+        self.clear_events();
+        for changed in self.changed.values_mut() {
+            changed.clear();
+        }
+        self.tick.0 += 1;
+    }
+
+    pub (crate) fn mark_changed_index(&mut self, component_index: usize, generation_less_index: usize) {
+        if let Some(changed) = self.changed.get_mut(&component_index) {
+            changed.add(bitset_index(generation_less_index), self.tick);
+        }
+    }
+
+    pub (crate) fn clear_changed_index(&mut self, generation_less_index: usize) {
+        for changed in self.changed.values_mut() {
+            changed.remove(bitset_index(generation_less_index));
+        }
+    }
+}
@@ -0,0 +1,64 @@
+/// How an `EntityList` allocates its memory, see `EntityList::with_config`.
+///
+/// Both the arena and the bitsets grow on their own when needed, so this only trades memory for
+/// fewer reallocations or lookups: start from `EntityListConfig::default()` and override what you
+/// need, for instance `EntityListConfig::default().with_lazy_bitsets(true)`. More options may be
+/// added later, so the config can't be built field by field outside of this crate.
+///
+/// Only `with_config` takes a config: lists built with `from_arena` or deserialized with serde use
+/// the default one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct EntityListConfig {
+    /// Number of entities every component bitset can hold before growing. Clamped to
+    /// `MAX_ENTITY_SLOTS`.
+    ///
+    /// Defaults to 64.
+    pub bitset_capacity: u32,
+    /// Number of entities the arena can hold before growing. Clamped to `MAX_ENTITY_SLOTS`.
+    ///
+    /// Defaults to 4, like `Arena::new`.
+    pub arena_capacity: usize,
+    /// If true, the bitset of a component is only created once an entity gets this component,
+    /// instead of creating the bitsets of all the components along with the list.
+    ///
+    /// Saves memory for entities declaring many rarely used components, at the cost of a lookup
+    /// every time a component is added. Querying a component whose bitset does not exist yet
+    /// yields no entities.
+    ///
+    /// Defaults to false.
+    pub lazy_bitsets: bool,
+}
+
+impl Default for EntityListConfig {
+    fn default() -> EntityListConfig {
+        EntityListConfig {
+            bitset_capacity: 64,
+            arena_capacity: 4,
+            lazy_bitsets: false,
+        }
+    }
+}
+
+impl EntityListConfig {
+    #[inline]
+    /// Returns this config with the given `bitset_capacity`.
+    pub fn with_bitset_capacity(mut self, bitset_capacity: u32) -> Self {
+        self.bitset_capacity = bitset_capacity;
+        self
+    }
+
+    #[inline]
+    /// Returns this config with the given `arena_capacity`.
+    pub fn with_arena_capacity(mut self, arena_capacity: usize) -> Self {
+        self.arena_capacity = arena_capacity;
+        self
+    }
+
+    #[inline]
+    /// Returns this config with the given `lazy_bitsets`.
+    pub fn with_lazy_bitsets(mut self, lazy_bitsets: bool) -> Self {
+        self.lazy_bitsets = lazy_bitsets;
+        self
+    }
+}
\ No newline at end of file
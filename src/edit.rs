@@ -0,0 +1,173 @@
+use std::ops::{Deref, DerefMut};
+
+use crate::{EntityBase, EntityId, EntityList};
+#[cfg(debug_assertions)]
+use crate::ComponentMask;
+use crate::iter::MultiComponent;
+
+/// A guard allowing to add and remove components freely via `get_mut`, returned by
+/// `EntityList::edit_session`.
+///
+/// Every entity retrieved mutably during the session is refreshed when the session is dropped,
+/// so only the bitsets of the touched entities are updated, instead of regenerating everything.
+/// The list can still be read during the session, but the bitsets may be out of date for the
+/// entities which have been touched so far.
+pub struct EditSession<'a, E: EntityBase> {
+    list: &'a mut EntityList<E>,
+    touched: Vec<EntityId>,
+}
+
+/// A handle to an entity yielded by `EntityList::iter_mut_safe`.
+///
+/// Unlike the `&mut E` yielded by `iter_mut`, components may be added or removed through this
+/// handle: every entity accessed mutably is refreshed once the iteration is over.
+pub struct EntityMut<'a, E: EntityBase> {
+    id: EntityId,
+    entity: &'a mut E,
+    touched: &'a mut bool,
+}
+
+/// A handle to an entity returned by `EntityList::get_mut_checked`.
+///
+/// Same as the `&mut E` returned by `get_mut`, but in debug builds, the components of the entity
+/// are remembered when the handle is created, and the handle panics when dropped if components
+/// have been added or removed in the meantime, instead of leaving the bitsets silently out of date.
+pub struct CheckedMut<'a, E: EntityBase> {
+    id: EntityId,
+    entity: &'a mut E,
+    #[cfg(debug_assertions)]
+    mask: ComponentMask<E>,
+}
+
+impl<E: EntityBase> EntityList<E> {
+    /// Same as `get_mut`, but catches components added or removed through the returned handle in
+    /// debug builds, see `CheckedMut`. In release builds, this is exactly `get_mut`.
+    pub fn get_mut_checked(&mut self, id: EntityId) -> Option<CheckedMut<'_, E>> {
+        self.entities.get_mut(id).map(|entity| CheckedMut {
+            id,
+            #[cfg(debug_assertions)]
+            mask: entity.component_mask(),
+            entity,
+        })
+    }
+
+    /// Starts an edit session, see `EditSession`.
+    pub fn edit_session(&mut self) -> EditSession<'_, E> {
+        EditSession {
+            list: self,
+            touched: Vec::new(),
+        }
+    }
+
+    /// Same as `iter_mut`, but calls `f` with an `EntityMut` handle for every matching entity,
+    /// which allows adding and removing components during the iteration.
+    ///
+    /// The bitsets of the entities accessed mutably are updated after the loop, so the changes
+    /// are not visible to queries until `iter_mut_safe` returns. Like with `replace`, all the
+    /// components of these entities are then marked as changed (see `track_changes`).
+    pub fn iter_mut_safe<C: for<'a> MultiComponent<'a, E>>(&mut self, mut f: impl FnMut(EntityMut<'_, E>)) {
+        let mut touched_ids: Vec<EntityId> = Vec::new();
+        for (id, entity) in self.iter_mut::<C>() {
+            let mut touched = false;
+            f(EntityMut { id, entity, touched: &mut touched });
+            if touched {
+                touched_ids.push(id);
+            }
+        }
+        for id in touched_ids {
+            self.refresh_replaced(id);
+        }
+    }
+}
+
+impl<'a, E: EntityBase> EntityMut<'a, E> {
+    #[inline]
+    /// Returns the id of the entity.
+    pub fn id(&self) -> EntityId {
+        self.id
+    }
+}
+
+impl<'a, E: EntityBase> Deref for EntityMut<'a, E> {
+    type Target = E;
+
+    fn deref(&self) -> &E {
+        self.entity
+    }
+}
+
+impl<'a, E: EntityBase> DerefMut for EntityMut<'a, E> {
+    fn deref_mut(&mut self) -> &mut E {
+        *self.touched = true;
+        self.entity
+    }
+}
+
+impl<'a, E: EntityBase> CheckedMut<'a, E> {
+    #[inline]
+    /// Returns the id of the entity.
+    pub fn id(&self) -> EntityId {
+        self.id
+    }
+}
+
+impl<'a, E: EntityBase> Deref for CheckedMut<'a, E> {
+    type Target = E;
+
+    fn deref(&self) -> &E {
+        self.entity
+    }
+}
+
+impl<'a, E: EntityBase> DerefMut for CheckedMut<'a, E> {
+    fn deref_mut(&mut self) -> &mut E {
+        self.entity
+    }
+}
+
+impl<'a, E: EntityBase> Drop for CheckedMut<'a, E> {
+    fn drop(&mut self) {
+        #[cfg(debug_assertions)]
+        {
+            // don't turn a panic in the middle of the mutation into an abort
+            if std::thread::panicking() {
+                return;
+            }
+            let mask: ComponentMask<E> = self.entity.component_mask();
+            if mask != self.mask {
+                panic!("!!!!FATAL: components of entity {:?} were changed via get_mut_checked (added: {:?}, removed: {:?}), \
+                    the bitsets are now out of date!!!!\n\
+                    Use add_component_for_entity and remove_component_for_entity, or edit_session instead!",
+                    self.id, mask.difference(&self.mask), self.mask.difference(&mask));
+            }
+        }
+    }
+}
+
+impl<'a, E: EntityBase> EditSession<'a, E> {
+    /// Retrieves an entity mutably. Unlike `EntityList::get_mut`, components may be added or
+    /// removed, the entity will be refreshed at the end of the session.
+    pub fn get_mut(&mut self, id: EntityId) -> Option<&mut E> {
+        let entity = self.list.entities.get_mut(id);
+        if entity.is_some() {
+            self.touched.push(id);
+        }
+        entity
+    }
+}
+
+impl<'a, E: EntityBase> Deref for EditSession<'a, E> {
+    type Target = EntityList<E>;
+
+    fn deref(&self) -> &EntityList<E> {
+        self.list
+    }
+}
+
+impl<'a, E: EntityBase> Drop for EditSession<'a, E> {
+    fn drop(&mut self) {
+        for id in self.touched.drain(..) {
+            self.list.refresh(id);
+        }
+    }
+}
\ No newline at end of file
@@ -1,7 +1,12 @@
-
-use std::any::TypeId;
+use crate::ComponentMask;
 
 pub trait Component<E: Sized>: 'static {
+    /// Position of this component in the `components` of `define_entity`, starting from 0.
+    ///
+    /// The bitsets of an `EntityList` are indexed by this, instead of the `TypeId` of the
+    /// component, whose value is not stable from a build to another.
+    const INDEX: usize;
+
     fn set(self, entity: &mut E);
 
     fn get(entity: &E) -> Option<&Self>;
@@ -73,6 +78,70 @@ pub trait Component<E: Sized>: 'static {
 /// }
 /// ```
 ///
+/// Components are identified by their type: every component of an entity must have its own type.
+/// If you need two components holding the same kind of data, for instance two `f32`, wrap them
+/// in newtypes, each of them will have its own bitset:
+///
+/// ```rust
+/// # use mobec::define_entity;
+/// #[derive(Clone)]
+/// pub struct Health(pub f32);
+///
+/// #[derive(Clone)]
+/// pub struct Mana(pub f32);
+///
+/// define_entity! {
+///     #[derive()]
+///     pub struct Entity {
+///         props => {},
+///         components => {
+///             health => Health,
+///             mana => Mana,
+///         }
+///     }
+/// }
+/// ```
+///
+/// Declaring two components of the same type is rejected at compile time, with an error about
+/// conflicting implementations of `Component<Entity>`, since both components would end up sharing
+/// the same bitset:
+///
+/// ```compile_fail,E0119
+/// # use mobec::define_entity;
+/// define_entity! {
+///     #[derive()]
+///     pub struct Entity {
+///         props => {},
+///         components => {
+///             health => f32,
+///             mana => f32,
+///         }
+///     }
+/// }
+/// ```
+///
+/// Likewise, a type cannot be both a property and a component of the same entity: the property
+/// would always be there while the component would be optional, which is confusing at best. This
+/// is rejected with an error about type annotations needed for `AmbiguousIfComponent`:
+///
+/// ```compile_fail,E0283
+/// # use mobec::define_entity;
+/// #[derive(Clone)]
+/// pub struct Health(pub f32);
+///
+/// define_entity! {
+///     #[derive()]
+///     pub struct Entity {
+///         props => {
+///             health: Health,
+///         },
+///         components => {
+///             regen => Health,
+///         }
+///     }
+/// }
+/// ```
+///
 /// You can derive just as many things as you'd like with a regular struct. Only `Copy` is forbidden
 /// if using components. Example:
 ///
@@ -84,63 +153,166 @@ pub trait Component<E: Sized>: 'static {
 ///         components => {}
 ///     }
 /// }
+/// ```
+///
+/// `Clone` is implemented by the macro (to forward `clone_from` to every field), so
+/// listing it in the `#[derive()]` attribute is allowed but has no effect:
+///
+/// ```rust
+/// # use mobec::define_entity;
+/// #[derive(Debug, Clone)]
+/// pub struct Health(pub f32);
+///
+/// define_entity! {
+///     #[derive(Debug, Clone)]
+///     pub struct Entity {
+///         props => {},
+///         components => {
+///             health => Health,
+///         }
+///     }
+/// }
+/// ```
+///
+/// Zero-sized marker components (for instance `pub struct Dead;`) can be prefixed with
+/// `#[marker]`. They are then stored as `Option<Dead>` instead of `Option<Box<Dead>>`: a `Box` of a
+/// zero-sized type never allocates, but the field still takes the size of a pointer, while
+/// `Option<Dead>` takes a single byte. Everything else, including the bitsets, works the same way.
+/// Using `#[marker]` on a type which is not zero-sized is a compile error.
+///
+/// ```rust
+/// # use mobec::define_entity;
+/// #[derive(Debug, Clone)]
+/// pub struct Health(pub f32);
+///
+/// #[derive(Debug, Clone)]
+/// pub struct Dead;
+///
+/// define_entity! {
+///     #[derive(Debug)]
+///     pub struct Entity {
+///         props => {},
+///         components => {
+///             health => Health,
+///             #[marker] dead => Dead,
+///         }
+///     }
+/// }
+/// ```
+///
+/// ```compile_fail,E0080
+/// # use mobec::define_entity;
+/// #[derive(Debug, Clone)]
+/// pub struct Health(pub f32);
+///
+/// define_entity! {
+///     #[derive(Debug)]
+///     pub struct Entity {
+///         props => {},
+///         components => {
+///             #[marker] health => Health,
+///         }
+///     }
+/// }
+/// ```
+///
+/// Components which should only live for a single tick, such as one-frame events, can be listed in
+/// an optional `events` section, after the components. They are regular components in every other
+/// way, but `EntityList::advance_tick` removes them from every entity:
+///
+/// ```rust
+/// # use mobec::define_entity;
+/// #[derive(Debug, Clone)]
+/// pub struct Health(pub f32);
+///
+/// #[derive(Debug, Clone)]
+/// pub struct Damaged { pub amount: f32 }
+///
+/// define_entity! {
+///     #[derive(Debug)]
+///     pub struct Entity {
+///         props => {},
+///         components => {
+///             health => Health,
+///         },
+///         events => {
+///             damaged => Damaged,
+///         }
+///     }
+/// }
+/// ```
+///
+/// If some of your props or components can't be cloned, add `#[no_clone]` right after the
+/// `#[derive()]` attribute to skip the generated `Clone` impl. The entity (and its
+/// `EntityList`) won't be `Clone` then, unless you derive or implement it yourself:
+///
+/// ```rust
+/// # use mobec::define_entity;
+/// pub struct FileHandle(pub std::fs::File);
+///
+/// define_entity! {
+///     #[derive()]
+///     #[no_clone]
+///     pub struct Entity {
+///         props => {},
+///         components => {
+///             file => FileHandle,
+///         }
+///     }
+/// }
+/// ```
+///
+/// With the `reflect` feature, adding `#[reflect]` as the last attribute (after `#[no_clone]`, if
+/// any) implements `ReflectEntity` for the entity, which gives access to its components as
+/// `bevy_reflect::Reflect`. Every component must then implement `Reflect`, for instance with
+/// `#[derive(Reflect)]`. Entities without `#[reflect]` are not affected by the feature.
+///
+/// `EntityBase::new` takes the props as a tuple, which is awkward for entities with one prop
+/// (`Entity::new((prop,))`) or none (`Entity::new(())`). Such entities get an inherent
+/// `Entity::new1(prop)` or `Entity::new0()` instead, with the same visibility as the entity.
+///
+/// ```rust
+/// # use mobec::define_entity;
+/// #[derive(Debug, Clone)]
+/// pub struct Name(pub String);
+///
+/// define_entity! {
+///     #[derive(Debug)]
+///     pub struct Entity {
+///         props => {
+///             name: Name,
+///         },
+///         components => {}
+///     }
+/// }
+///
+/// let entity = Entity::new1(Name(String::from("orc")));
+/// ```
 #[macro_export]
 macro_rules! define_entity {
-    (   #[derive( $( $derivety:path ),* ) ]
-        $vis:vis struct $entityname:ident {
-            props => {
-                $( $propname:ident : $propt:ty),* $(,)*
-            } $(,)?
-            components => {
-                $( $componentname:ident => $componenttype:ty ),* $(,)*
-            } $(,)?
-        }
+    (   #[derive( $( $derive:tt )* ) ]
+        #[no_clone]
+        $( $rest:tt )*
     ) => {
-
-        #[derive( $( $derivety ),* )]
-        $vis struct $entityname {
-            $(
-                pub $propname : $propt,
-            )*
-            $(
-                pub $componentname: Option<Box<$componenttype>>,
-            )*
-        }
-
-        $(
-            impl mobec::Component<$entityname> for $componenttype {
-                #[inline]
-                fn set(self, entity: &mut $entityname) {
-                    entity.$componentname = Some(Box::new(self))
-                }
-
-                #[inline]
-                fn get(entity: &$entityname) -> Option<&$componenttype> {
-                    entity.$componentname.as_ref().map(|s| &**s)
-                }
-
-                #[inline]
-                fn get_mut(entity: &mut $entityname) -> Option<&mut $componenttype> {
-                    entity.$componentname.as_mut().map(|s| &mut **s)
-                }
-
-                #[inline]
-                fn remove(entity: &mut $entityname) -> Option<Box<$componenttype>> {
-                    entity.$componentname.take()
-                }
-
-                #[inline]
-                fn peek<O, F: FnOnce(&Self) -> O>(entity: &$entityname, f: F) -> Option<O> {
-                    entity.$componentname.as_ref().map(|c| &**c).map(f)
-                }
-
-                #[inline]
-                fn update<O, F: FnOnce(&mut Self) -> O>(entity: &mut $entityname, f: F) -> Option<O> {
-                    entity.$componentname.as_mut().map(|c| &mut **c).map(f)
-                }
-            }
-        )*
-
+        $crate::define_entity!{ @entity no_clone [ $( $derive )* ] $( $rest )* }
+    };
+    (   #[derive( $( $derive:tt )* ) ]
+        $( $rest:tt )*
+    ) => {
+        $crate::define_entity!{ @strip_clone [] [ $( $derive )* ] $( $rest )* }
+    };
+    // Clone is implemented by hand below, it must not be derived as well.
+    ( @strip_clone [ $( $kept:tt )* ] [ Clone $( , $( $tail:tt )* )? ] $( $rest:tt )* ) => {
+        $crate::define_entity!{ @strip_clone [ $( $kept )* ] [ $( $( $tail )* )? ] $( $rest )* }
+    };
+    ( @strip_clone [ $( $kept:tt )* ] [ $derivety:path $( , $( $tail:tt )* )? ] $( $rest:tt )* ) => {
+        $crate::define_entity!{ @strip_clone [ $( $kept )* $derivety, ] [ $( $( $tail )* )? ] $( $rest )* }
+    };
+    ( @strip_clone [ $( $kept:tt )* ] [] $( $rest:tt )* ) => {
+        $crate::define_entity!{ @entity clone [ $( $kept )* ] $( $rest )* }
+    };
+    ( @clone no_clone $entityname:ident [ $( $propname:ident )* ] [ $( $componentname:ident )* ] ) => {};
+    ( @clone clone $entityname:ident [ $( $propname:ident )* ] [ $( $componentname:ident )* ] ) => {
         impl Clone for $entityname {
             fn clone(&self) -> Self {
                 Self {
@@ -162,6 +334,165 @@ macro_rules! define_entity {
                 )*
             }
         }
+    };
+    ( @reflect [] $entityname:ident [ $( $componentname:ident => $componenttype:ty, )* ] ) => {};
+    ( @reflect [reflect] $entityname:ident [ $( $componentname:ident => $componenttype:ty, )* ] ) => {
+        impl $crate::ReflectEntity for $entityname {
+            fn reflect_component(&self, type_id: std::any::TypeId) -> Option<&dyn $crate::bevy_reflect::Reflect> {
+                $(
+                    if type_id == std::any::TypeId::of::<$componenttype>() {
+                        return self.$componentname.as_ref()
+                            .map(|c| <_ as std::borrow::Borrow<$componenttype>>::borrow(c) as &dyn $crate::bevy_reflect::Reflect);
+                    }
+                )*
+                None
+            }
+        }
+    };
+    ( @new_helpers $vis:vis $entityname:ident [] ) => {
+        // the helpers may well go unused for private entities
+        #[allow(dead_code)]
+        impl $entityname {
+            #[inline]
+            /// Same as `EntityBase::new(())`, for entities without props.
+            $vis fn new0() -> Self {
+                <Self as $crate::EntityBase>::new(())
+            }
+        }
+    };
+    ( @new_helpers $vis:vis $entityname:ident [ $propname:ident : $propt:ty, ] ) => {
+        // the helpers may well go unused for private entities
+        #[allow(dead_code)]
+        impl $entityname {
+            #[inline]
+            /// Same as `EntityBase::new((prop,))`, for entities with a single prop.
+            $vis fn new1($propname: $propt) -> Self {
+                <Self as $crate::EntityBase>::new(($propname,))
+            }
+        }
+    };
+    ( @new_helpers $vis:vis $entityname:ident [ $( $props:tt )* ] ) => {};
+    ( @storage [] $componenttype:ty ) => { Option<Box<$componenttype>> };
+    ( @storage [marker] $componenttype:ty ) => { Option<$componenttype> };
+    ( @check_storage [] $componenttype:ty ) => {};
+    ( @check_storage [marker] $componenttype:ty ) => {
+        const _: () = assert!(std::mem::size_of::<$componenttype>() == 0, "#[marker] components must be zero-sized");
+    };
+    // Events are regular components, which are also listed separately to be cleared every tick.
+    (   @entity $clone:ident [ $( $derivety:tt )* ]
+        $( #[$reflect:ident] )?
+        $vis:vis struct $entityname:ident {
+            props => {
+                $( $propname:ident : $propt:ty),* $(,)*
+            } $(,)?
+            components => {
+                $( $( #[$storage:ident] )? $componentname:ident => $componenttype:ty ),* $(,)*
+            } $(,)?
+            $(
+                events => {
+                    $( $( #[$eventstorage:ident] )? $eventname:ident => $eventtype:ty ),* $(,)*
+                } $(,)?
+            )?
+        }
+    ) => {
+        $crate::define_entity!{
+            @entity_impl $clone [ $( $reflect )? ] [ $( $derivety )* ] [ $( $( $eventname => $eventtype, )* )? ]
+            $vis struct $entityname {
+                props => {
+                    $( $propname : $propt, )*
+                },
+                components => {
+                    $( $( #[$storage] )? $componentname => $componenttype, )*
+                    $( $( $( #[$eventstorage] )? $eventname => $eventtype, )* )?
+                }
+            }
+        }
+    };
+    (   @entity_impl $clone:ident [ $( $reflect:ident )? ] [ $( $derivety:tt )* ] [ $( $eventname:ident => $eventtype:ty, )* ]
+        $vis:vis struct $entityname:ident {
+            props => {
+                $( $propname:ident : $propt:ty),* $(,)*
+            } $(,)?
+            components => {
+                $( $( #[$storage:ident] )? $componentname:ident => $componenttype:ty ),* $(,)*
+            } $(,)?
+        }
+    ) => {
+
+        #[derive( $( $derivety )* )]
+        $vis struct $entityname {
+            $(
+                pub $propname : $propt,
+            )*
+            $(
+                pub $componentname: $crate::define_entity!(@storage [ $( $storage )? ] $componenttype),
+            )*
+        }
+
+        // Fails to compile if a property is also a component: `some_item` is ambiguous only for
+        // types implementing `Component<Entity>`.
+        const _: fn() = || {
+            trait AmbiguousIfComponent<A> {
+                fn some_item() {}
+            }
+            impl<T: ?Sized> AmbiguousIfComponent<()> for T {}
+            struct IsComponent;
+            impl<T: ?Sized + mobec::Component<$entityname>> AmbiguousIfComponent<IsComponent> for T {}
+            $(
+                let _ = <$propt as AmbiguousIfComponent<_>>::some_item;
+            )*
+        };
+
+        // Components are either stored as `Option<Box<C>>` or as `Option<C>` (for markers), going
+        // through `Borrow` and `From` lets the same code handle both.
+        const _: () = {
+            // the discriminants give the index of every component
+            #[allow(non_camel_case_types)]
+            enum __ComponentIndex {
+                $( $componentname, )*
+            }
+            $(
+                $crate::define_entity!{ @check_storage [ $( $storage )? ] $componenttype }
+
+                impl mobec::Component<$entityname> for $componenttype {
+                    const INDEX: usize = __ComponentIndex::$componentname as usize;
+
+                    #[inline]
+                    fn set(self, entity: &mut $entityname) {
+                        entity.$componentname = Some(<_ as From<$componenttype>>::from(self))
+                    }
+
+                    #[inline]
+                    fn get(entity: &$entityname) -> Option<&$componenttype> {
+                        entity.$componentname.as_ref().map(<_ as std::borrow::Borrow<$componenttype>>::borrow)
+                    }
+
+                    #[inline]
+                    fn get_mut(entity: &mut $entityname) -> Option<&mut $componenttype> {
+                        entity.$componentname.as_mut().map(<_ as std::borrow::BorrowMut<$componenttype>>::borrow_mut)
+                    }
+
+                    #[inline]
+                    fn remove(entity: &mut $entityname) -> Option<Box<$componenttype>> {
+                        entity.$componentname.take().map(<Box<$componenttype> as From<_>>::from)
+                    }
+
+                    #[inline]
+                    fn peek<O, F: FnOnce(&Self) -> O>(entity: &$entityname, f: F) -> Option<O> {
+                        entity.$componentname.as_ref().map(<_ as std::borrow::Borrow<Self>>::borrow).map(f)
+                    }
+
+                    #[inline]
+                    fn update<O, F: FnOnce(&mut Self) -> O>(entity: &mut $entityname, f: F) -> Option<O> {
+                        entity.$componentname.as_mut().map(<_ as std::borrow::BorrowMut<Self>>::borrow_mut).map(f)
+                    }
+                }
+            )*
+        };
+
+        $crate::define_entity!{ @clone $clone $entityname [ $( $propname )* ] [ $( $componentname )* ] }
+        $crate::define_entity!{ @reflect [ $( $reflect )? ] $entityname [ $( $componentname => $componenttype, )* ] }
+        $crate::define_entity!{ @new_helpers $vis $entityname [ $( $propname : $propt, )* ] }
 
         impl mobec::EntityBase for $entityname {
             type CreationParams = ( $( $propt ,)* );
@@ -177,25 +508,91 @@ macro_rules! define_entity {
                 }
             }
 
-            fn for_each_active_component(&self, mut f: impl FnMut(std::any::TypeId)) {
+            fn for_each_active_component(&self, mut f: impl FnMut(usize)) {
                 $(
                     if self.$componentname.is_some() {
-                        f(std::any::TypeId::of::< $componenttype >())
+                        f(<$componenttype as mobec::Component<Self>>::INDEX)
                     };
                 )*
             }
 
-            fn for_each_component(&self, mut f: impl FnMut(std::any::TypeId, bool)) {
+            fn for_each_component(&self, mut f: impl FnMut(usize, bool)) {
                 $(
-                    f(std::any::TypeId::of::< $componenttype >(), self.$componentname.is_some());
+                    f(<$componenttype as mobec::Component<Self>>::INDEX, self.$componentname.is_some());
                 )*
             }
 
-            fn for_all_components(mut f: impl FnMut(std::any::TypeId)) {
-                // todo, replace this by const once TypeId::of is a const fn
-                let components_type_ids: &[std::any::TypeId] = &[$( std::any::TypeId::of::<$componenttype>() ),*];
-                for component_id in components_type_ids {
-                    f(*component_id);
+            fn for_all_components(mut f: impl FnMut(usize)) {
+                $(
+                    f(<$componenttype as mobec::Component<Self>>::INDEX);
+                )*
+            }
+
+            fn for_all_events(mut f: impl FnMut(usize)) {
+                $(
+                    f(<$eventtype as mobec::Component<Self>>::INDEX);
+                )*
+            }
+
+            fn clear_events(&mut self) {
+                $(
+                    self.$eventname = None;
+                )*
+            }
+
+            fn clear_all_components(&mut self) {
+                $(
+                    self.$componentname = None;
+                )*
+            }
+
+            fn component_name(component_index: usize) -> Option<&'static str> {
+                let names: &[&'static str] = &[$( stringify!($componentname) ),*];
+                names.get(component_index).copied()
+            }
+
+            fn component_type_id(component_index: usize) -> Option<std::any::TypeId> {
+                let type_ids: &[std::any::TypeId] = &[$( std::any::TypeId::of::<$componenttype>() ),*];
+                type_ids.get(component_index).copied()
+            }
+
+            fn get_component_any(&self, type_id: std::any::TypeId) -> Option<&dyn std::any::Any> {
+                $(
+                    if type_id == std::any::TypeId::of::<$componenttype>() {
+                        return self.$componentname.as_ref()
+                            .map(|c| <_ as std::borrow::Borrow<$componenttype>>::borrow(c) as &dyn std::any::Any);
+                    }
+                )*
+                None
+            }
+
+            fn get_component_any_mut(&mut self, type_id: std::any::TypeId) -> Option<&mut dyn std::any::Any> {
+                $(
+                    if type_id == std::any::TypeId::of::<$componenttype>() {
+                        return self.$componentname.as_mut()
+                            .map(|c| <_ as std::borrow::BorrowMut<$componenttype>>::borrow_mut(c) as &mut dyn std::any::Any);
+                    }
+                )*
+                None
+            }
+
+            // generic parameters are prefixed so they don't shadow components named `C1` or `C2`
+            fn get_two_mut<__C1: mobec::Component<Self>, __C2: mobec::Component<Self>>(&mut self) -> Option<(&mut __C1, &mut __C2)> {
+                let mut c1: Option<&mut __C1> = None;
+                let mut c2: Option<&mut __C2> = None;
+                $(
+                    if let Some(c) = self.$componentname.as_mut() {
+                        let c: &mut dyn std::any::Any = <_ as std::borrow::BorrowMut<$componenttype>>::borrow_mut(c);
+                        if c.is::<__C1>() {
+                            c1 = c.downcast_mut::<__C1>();
+                        } else if c.is::<__C2>() {
+                            c2 = c.downcast_mut::<__C2>();
+                        }
+                    };
+                )*
+                match (c1, c2) {
+                    (Some(c1), Some(c2)) => Some((c1, c2)),
+                    _ => None,
                 }
             }
         }
@@ -209,6 +606,9 @@ pub enum ChangeComponent<C> {
     Replace(C),
     /// Mutate the currently available component. Only works if there is a component to begin with.
     Mutate(Box<dyn FnOnce(&mut C)>),
+    /// Mutate the currently available component. If there is no component to begin with, one is
+    /// created with the second function first, and then mutated.
+    MutateOrInsert(Box<dyn FnOnce(&mut C)>, Box<dyn FnOnce() -> C>),
     /// Remove the component without adding a new one.
     Remove,
 }
@@ -228,15 +628,70 @@ pub trait EntityBase: Sized + 'static {
     /// * and so on
     fn new(params: Self::CreationParams) -> Self;
 
-    // For a specific entity, go through every component this entity has.
-    fn for_each_active_component(&self, f: impl FnMut(TypeId));
+    // For a specific entity, go through every component this entity has. Components are given
+    // by their index, see `Component::INDEX`.
+    fn for_each_active_component(&self, f: impl FnMut(usize));
 
     // For a specific entity, go through every component this entity may have. A boolean
     // is attached to know whether the component is actually there or not.
-    fn for_each_component(&self, f: impl FnMut(TypeId, bool));
+    fn for_each_component(&self, f: impl FnMut(usize, bool));
 
     // Go through all possible components this kind of entity might have.
-    fn for_all_components(f: impl FnMut(TypeId));
+    fn for_all_components(f: impl FnMut(usize));
+
+    // Go through all the components of this kind of entity which are events. Events are also
+    // visited by `for_all_components`.
+    fn for_all_events(_f: impl FnMut(usize)) {}
+
+    /// Removes all the event components of this entity.
+    ///
+    /// Does not update the bitsets of any `EntityList`, see `EntityList::clear_events` instead.
+    fn clear_events(&mut self) {}
+
+    /// Removes all the components of this entity, leaving only its properties.
+    ///
+    /// Does not update the bitsets of any `EntityList`, see `EntityList::recycle` instead.
+    fn clear_all_components(&mut self);
+
+    /// Returns the field name of the component with the given index (see `Component::INDEX`), or
+    /// `None` if this entity has less components.
+    ///
+    /// Mostly useful for diagnostics, which would otherwise only be able to show opaque indices.
+    fn component_name(component_index: usize) -> Option<&'static str>;
+
+    /// Returns the `TypeId` of the component with the given index (see `Component::INDEX`), or
+    /// `None` if this entity has less components.
+    fn component_type_id(component_index: usize) -> Option<std::any::TypeId>;
+
+    /// Returns the component with the given `TypeId`, if this entity has it.
+    ///
+    /// Meant for tooling (editors, inspectors) which doesn't know the component types at compile
+    /// time: the result can be downcast back to the component with `Any::downcast_ref`.
+    fn get_component_any(&self, type_id: std::any::TypeId) -> Option<&dyn std::any::Any>;
+
+    /// Returns the component with the given `TypeId` mutably, if this entity has it.
+    ///
+    /// See `get_component_any`. Components cannot be added or removed this way.
+    fn get_component_any_mut(&mut self, type_id: std::any::TypeId) -> Option<&mut dyn std::any::Any>;
+
+    /// Returns the index (see `Component::INDEX`) of the component with the given `TypeId`, or
+    /// `None` if it is not a component of this entity.
+    ///
+    /// Goes through `for_all_components` and compares every `component_type_id`.
+    fn component_index_of(type_id: std::any::TypeId) -> Option<usize> {
+        let mut found: Option<usize> = None;
+        Self::for_all_components(|component_index: usize| {
+            if found.is_none() && Self::component_type_id(component_index) == Some(type_id) {
+                found = Some(component_index);
+            }
+        });
+        found
+    }
+
+    /// Retrieve two different components of the same entity mutably at the same time.
+    ///
+    /// Returns `None` if either component is missing, or if `C1` and `C2` are the same component.
+    fn get_two_mut<C1: Component<Self>, C2: Component<Self>>(&mut self) -> Option<(&mut C1, &mut C2)>;
 
     #[inline]
     /// Returns the ntity with the specified component. The old component is discarded.
@@ -265,8 +720,9 @@ pub trait EntityBase: Sized + 'static {
     /// Depending on the current state of the component for the given entity, do some compelx operations.
     ///
     /// You must give a predicate that takes a `&mut Entity`, and returns a `ChangeComponent`.
-    /// This is an enum that has four variants: one to change nothing, one to remove the component,
-    /// one to replace (or add) a component, and another to mutate an already existing component.
+    /// This is an enum that has five variants: one to change nothing, one to remove the component,
+    /// one to replace (or add) a component, one to mutate an already existing component, and a last
+    /// one to mutate a component, inserting it first if needed.
     ///
     /// In all cases, the entity is returned. This is very useful if you have a component that is a "computed"
     /// value depending on other components.
@@ -297,7 +753,49 @@ pub trait EntityBase: Sized + 'static {
                 };
                 self
             },
+            ChangeComponent::MutateOrInsert(f, insert) => {
+                if !self.has::<C>() {
+                    self.add(insert());
+                }
+                if let Some(c) = self.get_mut::<C>() {
+                    f(c)
+                };
+                self
+            },
+        }
+    }
+
+    /// Same as `with_component_change`, but the predicate also returns a custom value of your choice,
+    /// which is returned along with the entity.
+    ///
+    /// This is useful to know what happened, for instance by returning the old value of the component
+    /// before it is removed or replaced.
+    fn with_component_change_returning<C: Component<Self>, O, F: FnOnce(&mut Self) -> (ChangeComponent<C>, O)>(mut self, f: F) -> (Self, O) {
+        let (change, o) = f(&mut self);
+        (self.with_component_change(move |_: &mut Self| change), o)
+    }
+
+    /// Same as `with_component_change`, but the predicate may fail.
+    ///
+    /// If the predicate returns an error, the error is returned and the entity is dropped.
+    /// Otherwise, the change is applied and the entity is returned.
+    fn try_with_component_change<C: Component<Self>, Err, F: FnOnce(&mut Self) -> Result<ChangeComponent<C>, Err>>(mut self, f: F) -> Result<Self, Err> {
+        let change = f(&mut self)?;
+        Ok(self.with_component_change(move |_: &mut Self| change))
+    }
+
+    /// Returns the component of the given type, computing it first if the entity doesn't have it.
+    ///
+    /// The predicate receives the entity as it is, so that the component may be derived from the
+    /// properties or other components of the entity. It is not called if the component already exists.
+    ///
+    /// If the entity is in an `EntityList`, the list must be refreshed afterwards.
+    fn ensure<C: Component<Self>, F: FnOnce(&Self) -> C>(&mut self, make: F) -> &mut C {
+        if !self.has::<C>() {
+            let c = make(self);
+            c.set(self);
         }
+        self.get_mut::<C>().expect("component was just set")
     }
 
     #[inline]
@@ -334,6 +832,15 @@ pub trait EntityBase: Sized + 'static {
         C::get_mut(self)
     }
 
+    #[inline]
+    /// Retrieve several components at once, as a tuple of `Option<&C>`.
+    ///
+    /// For instance, `entity.get_all::<(A, B)>()` returns `(Option<&A>, Option<&B>)`. Even for a
+    /// single component, a tuple must be used: `get_all::<(A,)>()`.
+    fn get_all<'a, T: ComponentTuple<'a, Self>>(&'a self) -> T::OptRefs {
+        T::get_all(self)
+    }
+
     #[inline]
     /// Remove a component from the given entity.
     ///
@@ -349,4 +856,75 @@ pub trait EntityBase: Sized + 'static {
     fn add<C: Component<Self>>(&mut self, c: C) {
         c.set(self);
     }
-}
\ No newline at end of file
+
+    /// Returns the set of components this entity has.
+    fn component_mask(&self) -> ComponentMask<Self> {
+        let mut mask = ComponentMask::new();
+        self.for_each_active_component(|component_index: usize| mask.insert_index(component_index));
+        mask
+    }
+
+    /// Returns the components of `target` this entity does not have.
+    ///
+    /// Useful for entities assembled step by step, which are complete once this is empty.
+    fn missing_components(&self, target: &ComponentMask<Self>) -> ComponentMask<Self> {
+        target.difference(&self.component_mask())
+    }
+
+    #[inline]
+    /// Add a component to the given entity, akin to `HashMap::insert`.
+    ///
+    /// If the entity already had a component of this type, the old component is returned.
+    fn insert<C: Component<Self>>(&mut self, c: C) -> Option<Box<C>> {
+        let old = C::remove(self);
+        c.set(self);
+        old
+    }
+}
+
+/// Entities which can check their own invariants, for instance "having `Speed` implies having `Position`".
+///
+/// This is meant to be implemented by hand, and checked via `EntityList::validate_all`, typically in
+/// tests or debug builds.
+pub trait ValidatableEntity: EntityBase {
+    /// Returns an error describing the broken invariant, if any.
+    fn validate(&self) -> Result<(), String>;
+}
+
+/// Trait used by `EntityBase::get_all`, implemented for every tuple of components.
+///
+/// Do not implement externally.
+pub trait ComponentTuple<'a, E: EntityBase> {
+    type OptRefs;
+
+    fn get_all(entity: &'a E) -> Self::OptRefs;
+}
+
+macro_rules! component_tuple_impl {
+    ($($ty:ident),*) => {
+        impl<'a, E: EntityBase, $($ty: Component<E>),*> ComponentTuple<'a, E> for ($($ty,)*) {
+            type OptRefs = ($(Option<&'a $ty>,)*);
+
+            fn get_all(entity: &'a E) -> Self::OptRefs {
+                ($($ty::get(entity),)*)
+            }
+        }
+    }
+}
+
+component_tuple_impl!(C1);
+component_tuple_impl!(C1, C2);
+component_tuple_impl!(C1, C2, C3);
+component_tuple_impl!(C1, C2, C3, C4);
+component_tuple_impl!(C1, C2, C3, C4, C5);
+component_tuple_impl!(C1, C2, C3, C4, C5, C6);
+component_tuple_impl!(C1, C2, C3, C4, C5, C6, C7);
+component_tuple_impl!(C1, C2, C3, C4, C5, C6, C7, C8);
+component_tuple_impl!(C1, C2, C3, C4, C5, C6, C7, C8, C9);
+component_tuple_impl!(C1, C2, C3, C4, C5, C6, C7, C8, C9, C10);
+component_tuple_impl!(C1, C2, C3, C4, C5, C6, C7, C8, C9, C10, C11);
+component_tuple_impl!(C1, C2, C3, C4, C5, C6, C7, C8, C9, C10, C11, C12);
+component_tuple_impl!(C1, C2, C3, C4, C5, C6, C7, C8, C9, C10, C11, C12, C13);
+component_tuple_impl!(C1, C2, C3, C4, C5, C6, C7, C8, C9, C10, C11, C12, C13, C14);
+component_tuple_impl!(C1, C2, C3, C4, C5, C6, C7, C8, C9, C10, C11, C12, C13, C14, C15);
+component_tuple_impl!(C1, C2, C3, C4, C5, C6, C7, C8, C9, C10, C11, C12, C13, C14, C15, C16);
\ No newline at end of file
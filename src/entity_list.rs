@@ -1,15 +1,108 @@
-use std::any::TypeId;
-use std::convert::TryInto;
+use std::collections::BTreeMap;
 
 use hashbrown::HashMap;
-use hibitset::{BitSet};
+use hibitset::{BitSet, BitSetLike};
 
 use generational_arena::{Arena, Index};
 
-use crate::{EntityBase, Component};
+use crate::{EntityBase, Component, EntityListConfig, Tick, ValidatableEntity};
+use crate::iter::MultiComponent;
+use crate::changes::ChangedSet;
+use crate::stats::{bitset_len, bitset_capacity};
 
 pub type EntityId = Index;
 
+/// The maximum number of entity slots of an `EntityList`.
+///
+/// This is the limit of the underlying bitsets: 2^24 on 64-bit targets, 2^20 on 32-bit targets.
+/// Removed entities free their slot for the next insertions, so this is the maximum number of
+/// entities alive at the same time.
+pub const MAX_ENTITY_SLOTS: usize = (std::mem::size_of::<usize>() * 8).pow(4);
+
+/// Why `EntityList::from_iter_with_ids` could not rebuild a list holding the given ids.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdReplayError {
+    /// Two entities have the same index.
+    DuplicateIndex(usize),
+    /// An index is `MAX_ENTITY_SLOTS` or more, so it does not fit in the bitsets.
+    IndexTooLarge(usize),
+    /// The ids of this generation could not have been handed out by a single list, given the ids
+    /// of the other generations.
+    Unreproducible(u64),
+}
+
+impl std::fmt::Display for IdReplayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            IdReplayError::DuplicateIndex(index) => write!(f, "index {} is used by two entities", index),
+            IdReplayError::IndexTooLarge(index) => write!(f, "index {} does not fit in the bitsets, the maximum is {}", index, MAX_ENTITY_SLOTS),
+            IdReplayError::Unreproducible(generation) => write!(f, "ids of generation {} cannot be reproduced", generation),
+        }
+    }
+}
+
+impl std::error::Error for IdReplayError {}
+
+/// Converts the index of an entity in the arena (see `Index::into_raw_parts`) to its index in the
+/// bitsets, for instance to look an entity up in the bitset returned by `MultiComponent::bitset`.
+///
+/// `try_insert` never lets an entity past `MAX_ENTITY_SLOTS`, so this can only fail for an arena
+/// built outside of the list (`from_arena`). Panicking is better than silently truncating the
+/// index, which would flag another entity in the bitsets.
+///
+/// # Panics
+///
+/// If `generation_less_index` is `MAX_ENTITY_SLOTS` or more.
+#[inline]
+pub fn bitset_index(generation_less_index: usize) -> u32 {
+    assert!(generation_less_index < MAX_ENTITY_SLOTS,
+        "entity index {} does not fit in the bitsets, the maximum is {}", generation_less_index, MAX_ENTITY_SLOTS);
+    generation_less_index as u32
+}
+
+/// Returns the bitset of the given component, or `None` if it doesn't exist (see
+/// `EntityListConfig::lazy_bitsets`).
+#[inline]
+pub (crate) fn get_bitset(bitsets: &[Option<BitSet>], component_index: usize) -> Option<&BitSet> {
+    bitsets.get(component_index).and_then(Option::as_ref)
+}
+
+#[inline]
+pub (crate) fn get_bitset_mut(bitsets: &mut [Option<BitSet>], component_index: usize) -> Option<&mut BitSet> {
+    bitsets.get_mut(component_index).and_then(Option::as_mut)
+}
+
+/// Iterates over the bitsets which exist, along with the index of their component.
+pub (crate) fn existing_bitsets(bitsets: &[Option<BitSet>]) -> impl Iterator<Item=(usize, &BitSet)> {
+    bitsets.iter()
+        .enumerate()
+        .filter_map(|(component_index, bitset)| bitset.as_ref().map(|bitset| (component_index, bitset)))
+}
+
+/// Replaces the bitset of the given component, growing `bitsets` if needed.
+fn set_bitset(bitsets: &mut Vec<Option<BitSet>>, component_index: usize, bitset: Option<BitSet>) {
+    if bitsets.len() <= component_index {
+        bitsets.resize_with(component_index + 1, || None);
+    }
+    bitsets[component_index] = bitset;
+}
+
+/// Returns the bitset of the given component, creating it first with lazy bitsets (see
+/// `EntityListConfig::lazy_bitsets`).
+///
+/// Otherwise every component declared in the entity gets a bitset when the list is created (see
+/// `init_bitsets`), and bitsets are never removed afterwards, so a missing bitset is a bug of this
+/// crate. Panicking is better than silently leaving the entity out of the queries.
+#[inline]
+fn component_bitset<E: EntityBase>(bitsets: &mut Vec<Option<BitSet>>, config: EntityListConfig, component_index: usize) -> &mut BitSet {
+    if config.lazy_bitsets && get_bitset(bitsets, component_index).is_none() {
+        set_bitset(bitsets, component_index, Some(BitSet::with_capacity(config.bitset_capacity.min(MAX_ENTITY_SLOTS as u32))));
+    }
+    get_bitset_mut(bitsets, component_index)
+        .unwrap_or_else(|| panic!("!!!!FATAL: no bitset for component {}!!!!",
+            E::component_name(component_index).unwrap_or("<unknown component>")))
+}
+
 /// The struct holding a list/array of entities.
 ///
 /// It is backed by a `generational_arena`, and a `hibitset`.
@@ -18,51 +111,308 @@ pub type EntityId = Index;
 ///
 /// * Creations and removals are mostly `O(1)`
 /// * Iteration is linear time (unless you specify the components you're looking for,
-/// where it is at worse the same, at best hundreds of time faster, thanks to hibitset).
-/// * IDs cannot be reused, but their memory space is reusable.
+///   where it is at worse the same, at best hundreds of time faster, thanks to hibitset).
+/// * IDs cannot be reused, but their memory space is reusable. IDs are told apart by a
+///   generation, which is a `u64` and would need to overflow for an id to be reused, see
+///   `generation_high_water`.
+/// * At most `MAX_ENTITY_SLOTS` entities can be alive at the same time. This is a hard limit of
+///   `hibitset`, which only handles `u32` indices, and only up to 2^24 of them: wider indices would
+///   require another bitset implementation.
 pub struct EntityList<E: EntityBase> {
-    pub (crate) bitsets: HashMap<TypeId, BitSet>,
+    /// Indexed by `Component::INDEX`, `None` for the lazy bitsets which don't exist yet.
+    pub (crate) bitsets: Vec<Option<BitSet>>,
     pub (crate) entities: Arena<E>,
+    pub (crate) changed: HashMap<usize, ChangedSet>,
+    pub (crate) tick: Tick,
+    /// Entities kept aside by `despawn_to_pool`, the last one is reused first.
+    pub (crate) pool: Vec<EntityId>,
+    /// The entities of `pool`, by index, so that whole-list iteration can skip them.
+    pub (crate) pooled: BitSet,
+    pub (crate) config: EntityListConfig,
 }
 
 impl<E: EntityBase> EntityList<E> {
     pub fn new() -> EntityList<E> {
+        EntityList::with_config(EntityListConfig::default())
+    }
+
+    /// Creates an empty `EntityList`, reserving as much memory as asked by `config`.
+    pub fn with_config(config: EntityListConfig) -> EntityList<E> {
         let mut l = EntityList {
-            bitsets: HashMap::new(),
-            entities: Arena::new(),
+            bitsets: Vec::new(),
+            entities: Arena::with_capacity(config.arena_capacity.min(MAX_ENTITY_SLOTS)),
+            changed: HashMap::new(),
+            tick: Tick::default(),
+            pool: Vec::new(),
+            pooled: BitSet::new(),
+            config,
         };
-        l.init_bitsets(None);
+        l.init_bitsets();
         l
     }
 
     /// Creates an `EntityList` from an arena.
     ///
-    /// The bitsets are all re-generated.
+    /// The bitsets are all re-generated, with the default `EntityListConfig`.
     pub fn from_arena(arena: Arena<E>) -> EntityList<E> {
+        EntityList::from_arena_with_config(arena, EntityListConfig::default())
+    }
+
+    /// Same as `from_arena`, but keeps the given config, so that lists derived from another one
+    /// (`clone_matching`, `partition`, ...) create their bitsets the same way.
+    pub (crate) fn from_arena_with_config(arena: Arena<E>, config: EntityListConfig) -> EntityList<E> {
         let mut l: EntityList<_> = EntityList {
-            bitsets: HashMap::new(),
+            bitsets: Vec::new(),
             entities: arena,
+            changed: HashMap::new(),
+            tick: Tick::default(),
+            pool: Vec::new(),
+            pooled: BitSet::new(),
+            config,
         };
         l.regenerate_all_component_bitsets();
         l
     }
 
+    /// Creates an `EntityList` holding the given entities at exactly the given ids, for instance to
+    /// load a save whose ids are referenced elsewhere.
+    ///
+    /// `generational_arena` cannot insert at a given index and generation, so the arena is
+    /// rebuilt by replaying insertions and removals, using clones of one of the entities as
+    /// placeholders. This has a few limits:
+    ///
+    /// * The generation of an arena only moves forward by removing an entity, so reproducing
+    ///   generation `g` takes `g` removals and insertions: the time taken grows with the number of
+    ///   slots *plus the highest generation* among the ids, not with the number of entities. Ids
+    ///   taken from a list which went through billions of removals take as long to load.
+    /// * The ids handed out by the list afterwards differ from the ones the original list would
+    ///   have handed out, but they never collide with the given ids.
+    /// * Ids taken from an actual `EntityList` can always be reproduced. Arbitrary ids may not
+    ///   be, since the arena hands out the slots in a fixed order.
+    ///
+    /// Returns an error if two entities share the same index, if an index does not fit in the
+    /// bitsets, or if the ids could not have been handed out by a single list.
+    pub fn from_iter_with_ids(entities: impl IntoIterator<Item=(EntityId, E)>) -> Result<EntityList<E>, IdReplayError> where E: Clone {
+        let mut entities: Vec<(usize, u64, E)> = entities.into_iter()
+            .map(|(id, e)| {
+                let (index, generation) = id.into_raw_parts();
+                (index, generation, e)
+            })
+            .collect();
+        // the generation of an arena only grows, so entities are inserted oldest first
+        entities.sort_by_key(|(index, generation, _e)| (*generation, *index));
+        let placeholder = match entities.first() {
+            Some((_index, _generation, e)) => e.clone(),
+            None => return Ok(EntityList::new()),
+        };
+        let slots = entities.iter().map(|(index, _generation, _e)| index + 1).max().unwrap_or(0);
+        if slots > MAX_ENTITY_SLOTS {
+            return Err(IdReplayError::IndexTooLarge(slots - 1));
+        }
+        let mut used = vec![false; slots];
+        for (index, _generation, _e) in &entities {
+            if std::mem::replace(&mut used[*index], true) {
+                return Err(IdReplayError::DuplicateIndex(*index));
+            }
+        }
+
+        let mut groups: Vec<(u64, Vec<usize>)> = Vec::new();
+        let mut slot_entities: Vec<Option<E>> = (0..slots).map(|_| None).collect();
+        for (index, generation, e) in entities {
+            match groups.last_mut() {
+                Some((group_generation, indices)) if *group_generation == generation => indices.push(index),
+                _ => groups.push((generation, vec![index])),
+            }
+            slot_entities[index] = Some(e);
+        }
+        let plan = plan_id_replay(&groups)?;
+
+        // The free list of the arena is a stack of the freed slots, on top of the slots that were
+        // never used, in order. Both are mirrored here.
+        let mut arena: Arena<E> = Arena::with_capacity(slots);
+        let mut placeholders: Vec<Option<EntityId>> = vec![None; slots];
+        let mut freed: Vec<usize> = Vec::new();
+        let mut fresh: usize = 0;
+        let mut generation: u64 = 0;
+        for ((group_generation, group), (removals, raise)) in groups.into_iter().zip(plan) {
+            let unreproducible = IdReplayError::Unreproducible(group_generation);
+            while fresh < raise {
+                placeholders[fresh] = Some(arena.insert(placeholder.clone()));
+                fresh += 1;
+            }
+            let mut removals = removals.into_iter().peekable();
+            let mut bumps = group_generation - generation - removals.len() as u64;
+            if bumps > 0 {
+                // removing and re-inserting a placeholder moves the generation forward
+                let bump_index = match removals.peek() {
+                    Some(index) => *index,
+                    None => {
+                        if placeholders.len() == fresh {
+                            placeholders.push(None);
+                        }
+                        placeholders[fresh] = Some(arena.insert(placeholder.clone()));
+                        fresh += 1;
+                        fresh - 1
+                    },
+                };
+                if removals.len() == 0 && group.binary_search(&bump_index).is_ok() {
+                    bumps -= 1;
+                    removals = vec![bump_index].into_iter().peekable();
+                }
+                for _ in 0..bumps {
+                    let placeholder_id = placeholders[bump_index].take().ok_or_else(|| unreproducible.clone())?;
+                    let placeholder = arena.remove(placeholder_id).ok_or_else(|| unreproducible.clone())?;
+                    placeholders[bump_index] = Some(arena.insert(placeholder));
+                }
+                generation += bumps;
+            }
+            for index in removals {
+                let placeholder_id = placeholders[index].take().ok_or_else(|| unreproducible.clone())?;
+                arena.remove(placeholder_id);
+                freed.push(index);
+                generation += 1;
+            }
+
+            let mut remaining = group.len();
+            while remaining > 0 {
+                let index = match freed.pop() {
+                    Some(index) => index,
+                    None => {
+                        fresh += 1;
+                        fresh - 1
+                    },
+                };
+                match group.binary_search(&index) {
+                    Ok(_) => {
+                        let e = slot_entities[index].take().expect("slot is only filled once");
+                        let id = arena.insert(e);
+                        if id.into_raw_parts() != (index, group_generation) {
+                            return Err(unreproducible);
+                        }
+                        remaining -= 1;
+                    },
+                    // a slot freed earlier would be re-used instead of a slot of the group, and
+                    // past the last slot, no slot of the group can be handed out anymore.
+                    Err(_) if index + 1 != fresh || index >= slots => return Err(unreproducible),
+                    Err(_) => {
+                        placeholders[index] = Some(arena.insert(placeholder.clone()));
+                    },
+                }
+            }
+        }
+
+        for placeholder_id in placeholders.into_iter().flatten() {
+            arena.remove(placeholder_id);
+        }
+        Ok(EntityList::from_arena(arena))
+    }
+
     /// Insert an entity.
     ///
     /// Returns the ID of the entity you've just inserted.
+    ///
+    /// # Panics
+    ///
+    /// If the list already holds `MAX_ENTITY_SLOTS` entities, see `try_insert`.
     pub fn insert(&mut self, entity: E) -> EntityId {
-        let mut type_ids: Vec<TypeId> = Vec::with_capacity(8);
-        entity.for_each_active_component(|type_id: TypeId| {
-            type_ids.push(type_id);
+        match self.try_insert(entity, MAX_ENTITY_SLOTS) {
+            Ok(entity_id) => entity_id,
+            Err(_) => panic!("EntityList is full, it cannot hold more than {} entities", MAX_ENTITY_SLOTS),
+        }
+    }
+
+    /// Insert an entity, unless the list already holds `max_len` entities, in which case the entity
+    /// is given back.
+    ///
+    /// Useful to enforce a hard budget on a list, for instance at most 10 000 bullets. The entity is
+    /// also given back if the list is full (see `MAX_ENTITY_SLOTS`), whatever `max_len` is.
+    ///
+    /// # Panics
+    ///
+    /// If the list was built with `from_arena`, from an arena with a capacity above
+    /// `MAX_ENTITY_SLOTS`: the entity might land in a slot the bitsets can't hold.
+    pub fn try_insert(&mut self, entity: E, max_len: usize) -> Result<EntityId, E> {
+        if self.len() >= max_len || self.entities.len() >= MAX_ENTITY_SLOTS {
+            return Err(entity);
+        }
+        let capacity = self.entities.capacity();
+        if self.entities.len() == capacity {
+            // the arena is full, and would double its capacity to make room. Grow it ourselves
+            // instead, so that it never holds a slot the bitsets can't hold: as long as the
+            // capacity stays within `MAX_ENTITY_SLOTS`, so does the index of every entity.
+            self.entities.reserve(capacity.max(1).min(MAX_ENTITY_SLOTS - capacity));
+        }
+        let mut component_indices: Vec<usize> = Vec::with_capacity(8);
+        entity.for_each_active_component(|component_index: usize| {
+            component_indices.push(component_index);
         });
         let entity_id = self.entities.insert(entity);
         let (generation_less_index, _) = entity_id.into_raw_parts();
-        for type_id in type_ids {
-            if let Some(bitset) = self.bitsets.get_mut(&type_id) {
-                bitset.add(generation_less_index as u32);
-            }
+        for component_index in component_indices {
+            component_bitset::<E>(&mut self.bitsets, self.config, component_index).add(bitset_index(generation_less_index));
+            self.mark_changed_index(component_index, generation_less_index);
+        }
+        self.debug_check_consistency();
+        Ok(entity_id)
+    }
+
+    /// Insert an entity, and retrieve it mutably right away.
+    ///
+    /// Returns the ID of the entity you've just inserted along with the entity. The bitsets are
+    /// already up to date, so the same warning as `get_mut` applies: do not add or remove
+    /// components via the returned reference.
+    pub fn insert_mut(&mut self, entity: E) -> (EntityId, &mut E) {
+        let entity_id = self.insert(entity);
+        let entity = self.entities.get_mut(entity_id).expect("entity was just inserted");
+        (entity_id, entity)
+    }
+
+    /// Replace the entity at `id` by another one, keeping the same id.
+    ///
+    /// The bitsets are updated for the components of the new entity, and all of them are marked
+    /// as changed. Returns the old entity, or `None` if `id` is stale or pooled (see
+    /// `despawn_to_pool`), in which case `new_entity` is dropped.
+    pub fn replace(&mut self, id: EntityId, new_entity: E) -> Option<E> {
+        if self.is_pooled(id) {
+            return None;
+        }
+        let old_entity = std::mem::replace(self.entities.get_mut(id)?, new_entity);
+        self.refresh_replaced(id);
+        Some(old_entity)
+    }
+
+    /// Swap the entities at `a` and `b`, keeping the ids where they are.
+    ///
+    /// The bitsets are updated for both entities, and all of their components are marked as
+    /// changed. Returns `false` and does nothing if either id is stale or pooled, or if `a` and `b`
+    /// are the same id.
+    pub fn swap(&mut self, a: EntityId, b: EntityId) -> bool {
+        if a == b || self.is_pooled(a) || self.is_pooled(b) {
+            return false;
+        }
+        match self.entities.get2_mut(a, b) {
+            (Some(entity_a), Some(entity_b)) => std::mem::swap(entity_a, entity_b),
+            _ => return false,
+        }
+        self.refresh_replaced(a);
+        self.refresh_replaced(b);
+        true
+    }
+
+    /// Refreshes the bitsets of an entity which was replaced as a whole, and marks all of its
+    /// components as changed.
+    pub (crate) fn refresh_replaced(&mut self, id: EntityId) {
+        let generation_less_index = id.into_raw_parts().0;
+        self.refresh(id);
+
+        let mut component_indices: Vec<usize> = Vec::with_capacity(8);
+        self.entities[id].for_each_active_component(|component_index: usize| {
+            component_indices.push(component_index);
+        });
+        self.clear_changed_index(generation_less_index);
+        for component_index in component_indices {
+            self.mark_changed_index(component_index, generation_less_index);
         }
-        entity_id
     }
 
     /// Remove an entity
@@ -71,11 +421,14 @@ impl<E: EntityBase> EntityList<E> {
     pub fn remove(&mut self, id: EntityId) -> Option<E> {
         if let Some(e) = self.entities.remove(id) {
             let generation_less_index = id.into_raw_parts().0;
-            e.for_each_active_component(|type_id: TypeId| {
-                if let Some(bitset) = self.bitsets.get_mut(&type_id) {
-                    bitset.remove(generation_less_index as u32);
-                }
+            e.for_each_active_component(|component_index: usize| {
+                component_bitset::<E>(&mut self.bitsets, self.config, component_index).remove(bitset_index(generation_less_index));
             });
+            self.clear_changed_index(generation_less_index);
+            if self.pooled.remove(bitset_index(generation_less_index)) {
+                self.pool.retain(|pooled_id| *pooled_id != id);
+            }
+            self.debug_check_consistency();
             Some(e)
         } else {
             None
@@ -86,18 +439,50 @@ impl<E: EntityBase> EntityList<E> {
         if let Some(e) = self.entities.get_mut(id) {
             let generation_less_index = id.into_raw_parts().0;
             let bitsets = &mut self.bitsets;
-            e.for_each_component(|type_id: TypeId, is_active: bool| {
-                if let Some(bitset) = bitsets.get_mut(&type_id) {
-                    if is_active {
-                        bitset.add(generation_less_index as u32);
-                    } else {
-                        bitset.remove(generation_less_index as u32);
-                    }
+            let config = self.config;
+            e.for_each_component(|component_index: usize, is_active: bool| {
+                if is_active {
+                    component_bitset::<E>(bitsets, config, component_index).add(bitset_index(generation_less_index));
+                } else if let Some(bitset) = get_bitset_mut(bitsets, component_index) {
+                    // a lazy bitset is not created just to remove nothing from it
+                    bitset.remove(bitset_index(generation_less_index));
                 }
             });
         }
     }
 
+    /// Removes all the components of the given entity, without removing the entity itself.
+    ///
+    /// Useful to recycle an entity instead of removing it and inserting a new one, the id stays
+    /// valid. Does nothing if the entity does not exist anymore.
+    pub fn recycle(&mut self, id: EntityId) {
+        if let Some(e) = self.entities.get_mut(id) {
+            e.clear_all_components();
+            self.refresh(id);
+        }
+    }
+
+    /// Rebuilds the bitset of the component `C` only, by checking every entity for `C`.
+    ///
+    /// Use this when you know only `C` has been added or removed out-of-band (via `get_mut`), it is
+    /// much cheaper than regenerating every bitset. The other bitsets are left untouched.
+    pub fn refresh_component<C: Component<E>>(&mut self) {
+        self.rebuild_bitset_for_component::<C>();
+    }
+
+    /// Creates the bitset of the component `C` if it doesn't exist yet, by checking every entity
+    /// for `C`.
+    ///
+    /// Only lazy bitsets (see `EntityListConfig::lazy_bitsets`) can be missing, this allocates one
+    /// ahead of time, for instance before a burst of insertions. Does nothing if the bitset already
+    /// exists, even if it is out of date: see `refresh_component` for that.
+    pub fn ensure_bitset<C: Component<E>>(&mut self) {
+        if get_bitset(&self.bitsets, C::INDEX).is_none() {
+            let bitset = self.scan_bitset::<C>();
+            set_bitset(&mut self.bitsets, C::INDEX, Some(bitset));
+        }
+    }
+
     #[inline]
     /// Retrives an entity immutably.
     pub fn get(&self, id: EntityId) -> Option<&E> {
@@ -112,29 +497,218 @@ impl<E: EntityBase> EntityList<E> {
     /// possibly not being iterated over!
     ///
     /// To add or remove a component for an entity, use `add_component_for_entity` and
-    /// `remove_component_for_entity`.
+    /// `remove_component_for_entity`. `get_mut_checked` catches this mistake in debug builds.
     pub fn get_mut(&mut self, id: EntityId) -> Option<&mut E> {
         self.entities.get_mut(id)
     }
 
+    #[inline]
+    /// Retrieves a component of an entity immutably.
+    ///
+    /// Returns `None` if the entity doesn't exist, or if it doesn't have the component.
+    pub fn get_component<C: Component<E>>(&self, id: EntityId) -> Option<&C> {
+        self.entities.get(id).and_then(C::get)
+    }
+
+    #[inline]
+    /// Retrieves the component with the given `TypeId` of an entity, see
+    /// `EntityBase::get_component_any`.
+    ///
+    /// Returns `None` if the entity doesn't exist, or if it doesn't have the component.
+    pub fn get_component_any(&self, id: EntityId, type_id: std::any::TypeId) -> Option<&dyn std::any::Any> {
+        self.entities.get(id).and_then(|e| e.get_component_any(type_id))
+    }
+
+    /// Retrieves the component with the given `TypeId` of an entity mutably, see
+    /// `EntityBase::get_component_any_mut`.
+    ///
+    /// Returns `None` if the entity doesn't exist, or if it doesn't have the component. Like
+    /// `get_component_mut`, this cannot add or remove components, so the bitsets always stay
+    /// valid. If changes of the component are tracked, it is marked as changed.
+    pub fn get_component_any_mut(&mut self, id: EntityId, type_id: std::any::TypeId) -> Option<&mut dyn std::any::Any> {
+        let component = self.entities.get_mut(id).and_then(|e| e.get_component_any_mut(type_id));
+        if component.is_some() {
+            let changed = &mut self.changed;
+            if let Some(changed) = E::component_index_of(type_id).and_then(|component_index| changed.get_mut(&component_index)) {
+                changed.add(bitset_index(id.into_raw_parts().0), self.tick);
+            }
+        }
+        component
+    }
+
+    /// Returns the `TypeId` of every component this kind of entity can have, whether any entity
+    /// has it or not, in the order of declaration.
+    ///
+    /// Useful for tooling, for instance to offer every component in an "add component" menu.
+    pub fn all_component_type_ids(&self) -> Vec<std::any::TypeId> {
+        let mut type_ids: Vec<std::any::TypeId> = Vec::new();
+        E::for_all_components(|component_index: usize| {
+            type_ids.extend(E::component_type_id(component_index));
+        });
+        type_ids
+    }
+
+    #[inline]
+    /// Reads a component of an entity with the given predicate.
+    ///
+    /// You may return a custom value in your predicate. If the entity exists and has the component,
+    /// your value is returned, otherwise `None` is returned.
+    pub fn peek_component<C: Component<E>, O>(&self, id: EntityId, f: impl FnOnce(&C) -> O) -> Option<O> {
+        self.entities.get(id).and_then(|e| C::peek(e, f))
+    }
+
+    /// Retrieves a component of an entity mutably.
+    ///
+    /// Returns `None` if the entity doesn't exist, or if it doesn't have the component. Unlike
+    /// `get_mut`, this cannot add or remove components, so the bitsets always stay valid.
+    /// If changes of `C` are tracked, the component is marked as changed.
+    pub fn get_component_mut<C: Component<E>>(&mut self, id: EntityId) -> Option<&mut C> {
+        let component = self.entities.get_mut(id).and_then(C::get_mut);
+        if component.is_some() {
+            if let Some(changed) = self.changed.get_mut(&C::INDEX) {
+                changed.add(bitset_index(id.into_raw_parts().0), self.tick);
+            }
+        }
+        component
+    }
+
+    /// Mutates a component of an entity with the given predicate.
+    ///
+    /// You may return a custom value in your predicate. If the entity exists and has the component,
+    /// your value is returned, otherwise `None` is returned. Components cannot be added or removed
+    /// this way, so the bitsets always stay valid. If changes of `C` are tracked, the component is
+    /// marked as changed.
+    pub fn mutate_component<C: Component<E>, O>(&mut self, id: EntityId, f: impl FnOnce(&mut C) -> O) -> Option<O> {
+        let o = self.entities.get_mut(id).and_then(|e| C::update(e, f));
+        if o.is_some() {
+            self.mark_changed_index(C::INDEX, id.into_raw_parts().0);
+        }
+        o
+    }
+
+    /// Retrieves an entity mutably, inserting a new one if there is no entity for the given id.
+    ///
+    /// If `id` is `None`, is not a valid id anymore or is pooled (see `despawn_to_pool`), a new
+    /// entity created with `make` is inserted.
+    /// Returns the id of the entity along with the entity, which makes singleton-style entities
+    /// easy to handle: store the returned id and give it back next time.
+    ///
+    /// The same warning as `get_mut` applies: do not add or remove components via the returned reference.
+    pub fn get_or_insert_with(&mut self, id: Option<EntityId>, make: impl FnOnce() -> E) -> (EntityId, &mut E) {
+        let id = match id {
+            Some(id) if self.entities.contains(id) && !self.is_pooled(id) => id,
+            _ => self.insert(make()),
+        };
+        let entity = self.entities.get_mut(id).expect("entity was just checked or inserted");
+        (id, entity)
+    }
+
     #[inline]
     /// Returns true if the id exists.
     pub fn contains(&self, id: EntityId) -> bool {
         self.entities.contains(id)
     }
 
+    /// Returns true if all the given ids exist.
+    ///
+    /// An empty slice of ids is always valid.
+    pub fn contains_all(&self, ids: &[EntityId]) -> bool {
+        ids.iter().all(|id| self.entities.contains(*id))
+    }
+
     #[inline]
-    /// Returns the number of entities in the list.
+    /// Returns the number of entities in the list, not counting the pooled ones (see
+    /// `despawn_to_pool`).
     pub fn len(&self) -> usize {
-        self.entities.len()
+        self.entities.len() - self.pool.len()
+    }
+
+    /// Returns the highest generation among the ids of the live entities, or 0 if the list is
+    /// empty.
+    ///
+    /// The generation of the arena grows by one every time an entity is removed, and is a `u64`,
+    /// so it cannot realistically overflow. If it did, stale ids could alias live entities
+    /// again: this is meant for servers running for a very long time to keep an eye on it.
+    pub fn generation_high_water(&self) -> u64 {
+        self.entities.iter()
+            .map(|(id, _e)| id.into_raw_parts().1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Checks that the bitset of every component holds exactly the entities having this component,
+    /// by scanning the whole arena.
+    ///
+    /// This is meant for tests and debugging. With the `consistency_checks` feature, it also runs
+    /// after every insertion, removal, `retain`, and addition or removal of a component, in debug
+    /// builds only.
+    ///
+    /// # Panics
+    ///
+    /// If a bitset is out of sync with the entities.
+    pub fn assert_consistent(&self) {
+        let mut counts: HashMap<usize, usize> = HashMap::new();
+        for (id, entity) in &self.entities {
+            let index = bitset_index(id.into_raw_parts().0);
+            entity.for_each_active_component(|component_index: usize| {
+                *counts.entry(component_index).or_insert(0) += 1;
+                let in_bitset = get_bitset(&self.bitsets, component_index).is_some_and(|bitset| bitset.contains(index));
+                assert!(in_bitset, "entity {:?} has the component {} but is missing from its bitset",
+                    id, E::component_name(component_index).unwrap_or("?"));
+            });
+        }
+        for (component_index, bitset) in existing_bitsets(&self.bitsets) {
+            let count = counts.get(&component_index).copied().unwrap_or(0);
+            assert_eq!(bitset_len(bitset), count, "the bitset of the component {} is out of sync",
+                E::component_name(component_index).unwrap_or("?"));
+        }
+    }
+
+    /// Runs `assert_consistent` if the `consistency_checks` feature is enabled, in debug builds.
+    #[inline]
+    fn debug_check_consistency(&self) {
+        #[cfg(all(debug_assertions, feature = "consistency_checks"))]
+        self.assert_consistent();
+    }
+
+    /// Remove entities until there are at most `max` entities left.
+    ///
+    /// Entities for which `choose_victim` returns true are removed first. If that is not enough,
+    /// the other entities are removed as well. In both cases, entities are removed in iteration order.
+    ///
+    /// Useful to keep a hard limit on things like particles.
+    pub fn cap(&mut self, max: usize, mut choose_victim: impl FnMut(&E) -> bool) {
+        if self.len() <= max {
+            return;
+        }
+        let excess = self.len() - max;
+        let mut victims: Vec<EntityId> = Vec::with_capacity(excess);
+        let mut others: Vec<EntityId> = Vec::new();
+        for (id, e) in self.iter_all() {
+            if choose_victim(e) {
+                if victims.len() < excess {
+                    victims.push(id);
+                }
+            } else if others.len() < excess {
+                others.push(id);
+            }
+        }
+        for id in victims.into_iter().chain(others).take(excess) {
+            self.remove(id);
+        }
     }
 
     /// Initialize bitsets for all components of entity E
     ///
-    /// Default capacity is 4096, and is applied for all bitsets.
-    pub (crate) fn init_bitsets(&mut self, capacity: Option<u32>) {
-        E::for_all_components(|type_id: TypeId| {
-            self.bitsets.insert(type_id, BitSet::with_capacity(capacity.unwrap_or(4096)));
+    /// The capacity is applied for all bitsets, see `EntityListConfig::bitset_capacity`. Does
+    /// nothing with lazy bitsets, they are created by `component_bitset` instead.
+    pub (crate) fn init_bitsets(&mut self) {
+        if self.config.lazy_bitsets {
+            return;
+        }
+        let capacity = self.config.bitset_capacity.min(MAX_ENTITY_SLOTS as u32);
+        E::for_all_components(|component_index: usize| {
+            set_bitset(&mut self.bitsets, component_index, Some(BitSet::with_capacity(capacity)));
         });
     }
 
@@ -142,62 +716,56 @@ impl<E: EntityBase> EntityList<E> {
     fn regenerate_all_component_bitsets(&mut self) {
         let capacity = self.entities.len();
 
-        E::for_all_components(|type_id: TypeId| {
-            self.bitsets.insert(type_id, BitSet::with_capacity(capacity as u32));
-        });
-        let mut bitsets: Vec<(TypeId, &mut BitSet)> = self.bitsets.iter_mut().map(|(k, v)| (*k, v)).collect::<Vec<_>>();
-        bitsets.sort_unstable_by(|(k1, _), (k2, _)| k1.cmp(k2));
+        self.bitsets.clear();
+        if !self.config.lazy_bitsets {
+            E::for_all_components(|component_index: usize| {
+                set_bitset(&mut self.bitsets, component_index, Some(BitSet::with_capacity(capacity.min(MAX_ENTITY_SLOTS) as u32)));
+            });
+        }
+        let bitsets = &mut self.bitsets;
+        let config = self.config;
         for (id, el) in &self.entities {
             let index = id.into_raw_parts().0;
-            el.for_each_active_component(|seek_type_id: TypeId| {
-                if let Ok(i) = bitsets.binary_search_by(|(tid, _)| tid.cmp(&seek_type_id)) {
-                    bitsets[i].1.add(index as u32);
-                } else {
-                    unreachable!()
-                }
+            el.for_each_active_component(|component_index: usize| {
+                component_bitset::<E>(bitsets, config, component_index).add(bitset_index(index));
             })
         }
     }
 
-    // Add a bitset for a specific component for all entities.
-    //
-    // Typically done at the very start of the ECS
-    #[allow(dead_code)]
-    pub (crate) fn add_bitset_for_component<C: Component<E>>(&mut self) {
-        let bitset_capacity: u32 = self.entities.capacity().try_into().expect("too many entities");
-        let mut bitset = BitSet::with_capacity(bitset_capacity);
-        for (entity_id, entity) in &self.entities {
-            if entity.has::<C>() {
-                bitset.add(entity_id.into_raw_parts().0 as u32);
-            }
+    /// Rebuilds the bitset of a specific component from scratch, by checking every entity.
+    ///
+    /// The bitset of every component is created in `init_bitsets`, this only replaces it. With
+    /// lazy bitsets, the bitset of a component no entity has is dropped instead.
+    pub (crate) fn rebuild_bitset_for_component<C: Component<E>>(&mut self) {
+        let bitset = self.scan_bitset::<C>();
+        if self.config.lazy_bitsets && bitset.is_empty() {
+            // no entity has C, so a lazy bitset doesn't need to exist
+            set_bitset(&mut self.bitsets, C::INDEX, None);
+            return;
         }
-        self.bitsets.insert(
-            TypeId::of::<C>(),
-            bitset
-        );
+        set_bitset(&mut self.bitsets, C::INDEX, Some(bitset));
     }
 
-    // Remove a bitset for a specific component for all entities.
-    //
-    // Returns true if the bitset was actually there and was removed
-    #[allow(dead_code)]
-    pub (crate) fn remove_bitset_for_component<C: Component<E>>(&mut self) -> bool {
-        let bitset_capacity: u32 = self.entities.capacity().try_into().expect("too many entities");
+    /// Builds a bitset of the entities having the component `C`, by checking every entity.
+    fn scan_bitset<C: Component<E>>(&self) -> BitSet {
+        let bitset_capacity = self.entities.capacity().min(MAX_ENTITY_SLOTS) as u32;
         let mut bitset = BitSet::with_capacity(bitset_capacity);
         for (entity_id, entity) in &self.entities {
             if entity.has::<C>() {
-                bitset.remove(entity_id.into_raw_parts().0 as u32);
+                bitset.add(bitset_index(entity_id.into_raw_parts().0));
             }
         }
-        self.bitsets.remove(
-            &TypeId::of::<C>()
-        ).is_some()
+        bitset
     }
 
     /// Add a component for the given entity.
     ///
-    /// If the entity does not exist anymore, `Some(component)` is returned.
+    /// If the entity does not exist anymore or is pooled (see `despawn_to_pool`), the component is
+    /// not added and `Some(component)` is returned.
     pub fn add_component_for_entity<C: Component<E>>(&mut self, entity_id: EntityId, component: C) -> Option<C> {
+        if self.is_pooled(entity_id) {
+            return Some(component);
+        }
         let maybe_component = match self.entities.get_mut(entity_id) {
             Some(e) => {
                 component.set(e);
@@ -209,12 +777,11 @@ impl<E: EntityBase> EntityList<E> {
         };
         // maybe_component is Some if it hasn't been applied, None if it has been applied.
         if maybe_component.is_none() {
-            // if it has been added, see if we have a bitset for this component
-            if let Some(bitset) = self.bitsets.get_mut(&TypeId::of::<C>()) {
-                // we have a bitset, so add the info that this entity has the given component
-                bitset.add(entity_id.into_raw_parts().0 as u32);
-            };
+            // if it has been added, add the info that this entity has the given component
+            component_bitset::<E>(&mut self.bitsets, self.config, C::INDEX).add(bitset_index(entity_id.into_raw_parts().0));
+            self.mark_changed_index(C::INDEX, entity_id.into_raw_parts().0);
         };
+        self.debug_check_consistency();
 
         maybe_component
     }
@@ -229,36 +796,290 @@ impl<E: EntityBase> EntityList<E> {
 
         // maybe_component is Some if it was a component, None if it wasn't.
         if maybe_component.is_some() {
-            // if it has been removed, see if we have a bitset for this component
-            if let Some(bitset) = self.bitsets.get_mut(&TypeId::of::<C>()) {
-                // we have a bitset, so remove the info that this entity has the given component
-                bitset.remove(entity_id.into_raw_parts().0 as u32);
-            };
+            // if it has been removed, remove the info that this entity has the given component
+            component_bitset::<E>(&mut self.bitsets, self.config, C::INDEX).remove(bitset_index(entity_id.into_raw_parts().0));
         };
+        self.debug_check_consistency();
 
         maybe_component
     }
 
-    /// Akin to Vec::retain, deletes entities where the predicate returns true
+    /// Remove the component `C` from every entity where the predicate returns true for its `C`.
+    ///
+    /// Only the entities having `C` are visited thanks to the bitsets. Returns the number of
+    /// removed components.
+    pub fn remove_component_where<C: Component<E>>(&mut self, pred: impl Fn(&C) -> bool) -> usize {
+        let ids: Vec<EntityId> = self.iter::<(C,)>()
+            .filter(|(_id, e)| e.get::<C>().is_some_and(&pred))
+            .map(|(id, _e)| id)
+            .collect();
+        for id in &ids {
+            self.remove_component_for_entity::<C>(*id);
+        }
+        ids.len()
+    }
+
+    /// Removes the component `C` from every entity, and returns the removed components along with
+    /// the ids of their entities, in iteration order.
+    ///
+    /// Only the entities having `C` are visited thanks to the bitsets, and the bitset of `C` is
+    /// cleared at once. Useful to hand a whole category of components over to another system.
+    pub fn drain_component<C: Component<E>>(&mut self) -> Vec<(EntityId, Box<C>)> {
+        let ids: Vec<EntityId> = self.iter::<(C,)>().map(|(id, _e)| id).collect();
+        let drained = ids.into_iter()
+            .filter_map(|id| self.entities.get_mut(id).and_then(C::remove).map(|c| (id, c)))
+            .collect();
+        if let Some(bitset) = get_bitset_mut(&mut self.bitsets, C::INDEX) {
+            bitset.clear();
+        }
+        self.debug_check_consistency();
+        drained
+    }
+
+    /// Sets the component `C` of every listed entity, for instance with results computed elsewhere.
+    ///
+    /// Ids of entities which don't exist anymore or are pooled are skipped. Existing components
+    /// are replaced.
+    pub fn apply_component_updates<C: Component<E>>(&mut self, updates: impl IntoIterator<Item=(EntityId, C)>) {
+        self.apply_component_updates_inner(updates, |_id| {});
+    }
+
+    /// Sets the component `C` of every entity in `results`, for instance when writing back the
+    /// results of a system computed outside of the list.
+    ///
+    /// Ids of entities which don't exist anymore are skipped, use `zip_apply_checked` to know
+    /// which ones.
+    pub fn zip_apply<C: Component<E> + Clone>(&mut self, results: &[(EntityId, C)]) {
+        self.apply_component_updates_inner(results.iter().cloned(), |_id| {});
+    }
+
+    /// Same as `zip_apply`, but returns the ids of the entities which don't exist anymore, in the
+    /// order they were given.
+    pub fn zip_apply_checked<C: Component<E> + Clone>(&mut self, results: &[(EntityId, C)]) -> Vec<EntityId> {
+        let mut stale: Vec<EntityId> = Vec::new();
+        self.apply_component_updates_inner(results.iter().cloned(), |id| stale.push(id));
+        stale
+    }
+
+    /// Copies the component `C` of entities of `other` into entities of this list.
+    ///
+    /// `id_map` yields pairs of ids, an id of `other` along with the matching id of this list, for
+    /// instance a `HashMap<EntityId, EntityId>`. Entities of `other` without `C`, and ids which
+    /// don't exist anymore on either side, are skipped. Existing components are replaced.
+    pub fn merge_component_from<C: Component<E> + Clone>(&mut self, other: &EntityList<E>, id_map: impl IntoIterator<Item=(EntityId, EntityId)>) {
+        let updates = id_map.into_iter()
+            .filter_map(|(other_id, self_id)| other.get_component::<C>(other_id).map(|c| (self_id, c.clone())));
+        self.apply_component_updates_inner(updates, |_id| {});
+    }
+
+    fn apply_component_updates_inner<C: Component<E>>(&mut self, updates: impl IntoIterator<Item=(EntityId, C)>, mut on_stale: impl FnMut(EntityId)) {
+        let component_index = C::INDEX;
+        let mut changed = self.changed.get_mut(&component_index);
+        let pooled = &self.pooled;
+        for (id, component) in updates {
+            let index = bitset_index(id.into_raw_parts().0);
+            match self.entities.get_mut(id) {
+                Some(e) if !pooled.contains(index) => {
+                    component.set(e);
+                    // looked up for every update, so that a lazy bitset is only created once an update applies
+                    component_bitset::<E>(&mut self.bitsets, self.config, component_index).add(index);
+                    if let Some(changed) = changed.as_mut() {
+                        changed.add(index, self.tick);
+                    }
+                },
+                _ => on_stale(id),
+            }
+        }
+    }
+
+    /// Split the list in two, according to the given predicate.
+    ///
+    /// The entities for which the predicate returns true end up in the first list, the others
+    /// in the second one. Both lists have their bitsets regenerated, but ids are *not* preserved,
+    /// and change tracking is not carried over. Pooled entities are dropped.
+    pub fn partition(self, mut pred: impl FnMut(&E) -> bool) -> (EntityList<E>, EntityList<E>) {
+        let mut left: Arena<E> = Arena::new();
+        let mut right: Arena<E> = Arena::new();
+        let mut entities = self.entities;
+        for (id, entity) in entities.drain() {
+            if self.pooled.contains(bitset_index(id.into_raw_parts().0)) {
+                continue;
+            }
+            if pred(&entity) {
+                left.insert(entity);
+            } else {
+                right.insert(entity);
+            }
+        }
+        (EntityList::from_arena_with_config(left, self.config), EntityList::from_arena_with_config(right, self.config))
+    }
+
+    /// Converts this list into a list of another entity type, by converting every entity with `f`.
+    ///
+    /// The fields of an entity are fixed, so a component can't be renamed or restructured within
+    /// the same entity type. Instead, declare the new entity type and convert every entity to it,
+    /// for instance when loading an old save format. The bitsets of the new list are built from the
+    /// converted entities and change tracking is not carried over.
+    ///
+    /// Ids are *not* preserved: the returned pairs map every old id to its new id, for instance to
+    /// fix the ids stored in components, or to feed `merge_component_from`. To keep the same ids,
+    /// pass the converted entities to `from_iter_with_ids` instead, with its limits. Pooled
+    /// entities are dropped.
+    pub fn migrate<E2: EntityBase>(self, mut f: impl FnMut(E) -> E2) -> (EntityList<E2>, Vec<(EntityId, EntityId)>) {
+        let mut arena: Arena<E2> = Arena::with_capacity(self.len());
+        let mut id_map: Vec<(EntityId, EntityId)> = Vec::with_capacity(self.len());
+        let mut entities = self.entities;
+        for (id, entity) in entities.drain() {
+            if !self.pooled.contains(bitset_index(id.into_raw_parts().0)) {
+                id_map.push((id, arena.insert(f(entity))));
+            }
+        }
+        (EntityList::from_arena_with_config(arena, self.config), id_map)
+    }
+
+    /// Creates a new list holding a clone of every entity matching the given components.
+    ///
+    /// The new list has its bitsets regenerated, but ids are *not* preserved.
+    pub fn clone_matching<C: for<'a> MultiComponent<'a, E>>(&self) -> EntityList<E> where E: Clone {
+        let mut arena: Arena<E> = Arena::new();
+        for (_id, entity) in self.iter::<C>() {
+            arena.insert(entity.clone());
+        }
+        EntityList::from_arena_with_config(arena, self.config)
+    }
+
+    /// Clones out the component `C` of every entity which has it, along with the id of the entity.
+    ///
+    /// Only the entities with `C` are visited thanks to the bitsets. Useful to snapshot a single
+    /// component for a subsystem.
+    pub fn collect_component<C: Component<E> + Clone>(&self) -> Vec<(EntityId, C)> {
+        self.iter::<(C,)>()
+            .filter_map(|(id, e)| C::get(e).map(|c| (id, c.clone())))
+            .collect()
+    }
+
+    /// Clones the list by cloning the entities only, the bitsets are regenerated from the clones.
+    ///
+    /// Ids and the pool are preserved, but change tracking is not carried over.
+    ///
+    /// This is *not* faster than `clone`: cloning the arena visits its free slots as well, so even
+    /// when most entities have been removed, regenerating the bitsets costs about as much as
+    /// cloning them, and up to 50% more for small lists (see the `clone` benchmark). It is mostly
+    /// useful when the bitsets are suspected to be out of sync with the entities.
+    pub fn clone_entities_only(&self) -> EntityList<E> where E: Clone {
+        let mut l = EntityList::from_arena_with_config(self.entities.clone(), self.config);
+        l.pool.clone_from(&self.pool);
+        l.pooled.clone_from(&self.pooled);
+        l
+    }
+
+    /// Remove all the entities matching the given components.
+    ///
+    /// Only the matching entities are visited thanks to the bitsets, so this is much faster than
+    /// `remove_where` when few entities match, for instance to remove every entity with a `Dead` marker.
+    ///
+    /// Returns the number of removed entities.
+    pub fn remove_query<C: for<'a> MultiComponent<'a, E>>(&mut self) -> usize {
+        let ids: Vec<EntityId> = self.iter::<C>().map(|(id, _e)| id).collect();
+        for id in &ids {
+            self.remove(*id);
+        }
+        ids.len()
+    }
+
+    /// Remove all the entities matching the given components, and yield them.
+    ///
+    /// The entities are all removed right away, even if the returned iterator is not consumed.
+    pub fn drain_query<C: for<'a> MultiComponent<'a, E>>(&mut self) -> impl Iterator<Item=(EntityId, E)> {
+        let ids: Vec<EntityId> = self.iter::<C>().map(|(id, _e)| id).collect();
+        let drained: Vec<(EntityId, E)> = ids.into_iter()
+            .filter_map(|id| self.remove(id).map(|e| (id, e)))
+            .collect();
+        drained.into_iter()
+    }
+
+    /// Akin to `Vec::retain`, keeps the entities where the predicate returns true, and removes the
+    /// others.
+    ///
+    /// Pooled entities are skipped and kept.
     pub fn retain(&mut self, mut predicate: impl FnMut(EntityId, &mut E) -> bool) {
+        self.remove_where(|id, e| !predicate(id, e));
+    }
+
+    /// The opposite of `retain`: removes the entities where the predicate returns true, and keeps
+    /// the others.
+    ///
+    /// Pooled entities are skipped and kept.
+    pub fn remove_where(&mut self, mut predicate: impl FnMut(EntityId, &mut E) -> bool) {
         let bitsets = &mut self.bitsets;
+        let config = self.config;
+        let changed = &mut self.changed;
+        let pooled = &self.pooled;
         self.entities.retain(|index, e| {
+            if pooled.contains(bitset_index(index.into_raw_parts().0)) {
+                return true;
+            }
             let should_delete = predicate(index, e);
             if should_delete {
-                e.for_each_active_component(|type_id: TypeId| {
-                    if let Some(bitset) = bitsets.get_mut(&type_id) {
-                        bitset.remove(index.clone().into_raw_parts().0 as u32);
-                    }
+                e.for_each_active_component(|component_index: usize| {
+                    component_bitset::<E>(bitsets, config, component_index).remove(bitset_index(index.into_raw_parts().0));
                 });
+                for changed_bitset in changed.values_mut() {
+                    changed_bitset.remove(bitset_index(index.into_raw_parts().0));
+                }
             }
-            should_delete
-        })
+            !should_delete
+        });
+        self.debug_check_consistency();
+    }
+}
+
+impl<E: ValidatableEntity> EntityList<E> {
+    /// Validates every entity of the list.
+    ///
+    /// Returns the first failure along with the id of the offending entity.
+    pub fn validate_all(&self) -> Result<(), (EntityId, String)> {
+        for (id, entity) in self.iter_all() {
+            entity.validate().map_err(|err| (id, err))?;
+        }
+        Ok(())
     }
 }
 
+/// The regular form (`{:?}`) only prints the entities.
+///
+/// The alternate form (`{:#?}`) also prints, for every component bitset (by component name), how
+/// many entities have the component and the capacity of the bitset.
 impl<E: EntityBase> std::fmt::Debug for EntityList<E> where E: std::fmt::Debug {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        self.entities.fmt(f)
+        if f.alternate() {
+            let bitsets: BTreeMap<&'static str, BitSetSummary> = existing_bitsets(&self.bitsets)
+                .map(|(component_index, bitset)| (E::component_name(component_index).unwrap_or("<unknown>"), BitSetSummary {
+                    len: bitset_len(bitset),
+                    capacity: bitset_capacity(bitset),
+                }))
+                .collect();
+            f.debug_struct("EntityList")
+                .field("entities", &self.entities)
+                .field("bitsets", &bitsets)
+                .finish()
+        } else {
+            self.entities.fmt(f)
+        }
+    }
+}
+
+struct BitSetSummary {
+    len: usize,
+    capacity: usize,
+}
+
+impl std::fmt::Debug for BitSetSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("BitSet")
+            .field("len", &self.len)
+            .field("capacity", &self.capacity)
+            .finish()
     }
 }
 
@@ -267,11 +1088,86 @@ impl<E: EntityBase> Clone for EntityList<E> where E: Clone {
         EntityList {
             bitsets: self.bitsets.clone(),
             entities: self.entities.clone(),
+            changed: self.changed.clone(),
+            tick: self.tick,
+            pool: self.pool.clone(),
+            pooled: self.pooled.clone(),
+            config: self.config,
         }
     }
 
     fn clone_from(&mut self, other: &Self) {
         self.bitsets.clone_from(&other.bitsets);
         self.entities.clone_from(&other.entities);
+        self.changed.clone_from(&other.changed);
+        self.tick = other.tick;
+        self.pool.clone_from(&other.pool);
+        self.pooled.clone_from(&other.pooled);
+        self.config = other.config;
+    }
+}
+/// Plans how `EntityList::from_iter_with_ids` replays the history of an arena, for entities grouped
+/// by generation (ascending) and sorted by index within a group.
+///
+/// Returns, for every group, the slots to free before inserting it, and the number of slots that
+/// must have been used before doing so. Returns an error if the groups can't be reproduced.
+///
+/// Every removal moves the generation forward by one, so the slots taken by a group are either
+/// freed since the previous group, freed earlier (and then handed out after the slots freed in
+/// between, since the free list is a stack), or never used before. Slots never used before can only
+/// be handed out when no freed slot is waiting on the stack, the other ones have to be filled
+/// with placeholders beforehand.
+///
+/// This is a fixed point: which slots were used before a group depends on which slots are freed,
+/// and the other way around. It terminates because every entry of `fresh_from` only grows (see
+/// `next_fresh_from`), and is either at most the number of slots or `usize::MAX`: every round but
+/// the last raises at least one entry, so there are at most `groups.len() * (slots + 2)` rounds.
+/// In practice, a few rounds are enough.
+fn plan_id_replay(groups: &[(u64, Vec<usize>)]) -> Result<Vec<(Vec<usize>, usize)>, IdReplayError> {
+    // slots of a group at or above this index are never used before the group is inserted
+    let mut fresh_from: Vec<usize> = vec![0; groups.len()];
+    loop {
+        let mut removals: Vec<Vec<usize>> = vec![Vec::new(); groups.len()];
+        // whether slots of later groups are already freed when a group is inserted
+        let mut waiting: Vec<bool> = vec![false; groups.len()];
+        let mut carried: Vec<usize> = Vec::new();
+        for (j, (generation, indices)) in groups.iter().enumerate().rev() {
+            waiting[j] = !carried.is_empty();
+            let previous = if j > 0 { groups[j - 1].0 } else { 0 };
+            let mut freed = std::mem::take(&mut carried);
+            let reused = indices.partition_point(|index| *index < fresh_from[j]);
+            // freed in reverse order, so that they are re-occupied in order.
+            freed.extend(indices[..reused].iter().rev());
+            let excess = (freed.len() as u64).saturating_sub(generation - previous) as usize;
+            carried = freed.drain(..excess).collect();
+            removals[j] = freed;
+        }
+        if !carried.is_empty() {
+            return Err(IdReplayError::Unreproducible(groups[0].0));
+        }
+
+        let mut raises: Vec<usize> = vec![0; groups.len()];
+        let mut next_fresh_from: Vec<usize> = Vec::with_capacity(groups.len());
+        let mut used: usize = 0;
+        for j in 0..groups.len() {
+            if j == 0 || !waiting[j - 1] {
+                // nothing is waiting on the stack, the slots of the groups inserted before the
+                // stack is empty again are filled now.
+                let last = (j..groups.len()).find(|k| !waiting[*k]).unwrap_or(groups.len());
+                used = groups[j..last].iter()
+                    .filter_map(|(_generation, indices)| indices.last().map(|index| index + 1))
+                    .fold(used, usize::max);
+                raises[j] = used;
+            }
+            let from = if waiting[j] { usize::MAX } else { used }.max(fresh_from[j]);
+            next_fresh_from.push(from);
+            if let Some(last) = groups[j].1.last().filter(|last| **last >= from) {
+                used = last + 1;
+            }
+        }
+        if next_fresh_from == fresh_from {
+            return Ok(removals.into_iter().zip(raises).collect());
+        }
+        fresh_from = next_fresh_from;
     }
 }
\ No newline at end of file
@@ -1,33 +1,248 @@
 use crate::{Component, EntityBase, EntityList, EntityId};
+use crate::entity_list::{bitset_index, get_bitset};
 use generational_arena::Arena;
 use hibitset::{BitIter, BitSet, BitSetLike, BitSetAll, BitSetAnd};
-use tuple_utils::Split;
 
-use std::any::TypeId;
-
-use hashbrown::HashMap;
+use std::sync::OnceLock;
 
 impl<E: EntityBase> EntityList<E> {
+    /// Iterate over every entity, except the pooled ones (see `despawn_to_pool`).
     pub fn iter_all<'a>(&'a self) -> impl Iterator<Item=(EntityId, &'a E)> {
+        let pooled = &self.pooled;
         self.entities.iter()
+            .filter(move |(id, _e)| !pooled.contains(bitset_index(id.into_raw_parts().0)))
     }
 
+    /// Same as `iter_all`, but mutable.
     pub fn iter_all_mut<'a>(&'a mut self) -> impl Iterator<Item=(EntityId, &'a mut E)> {
+        let pooled = &self.pooled;
         self.entities.iter_mut()
+            .filter(move |(id, _e)| !pooled.contains(bitset_index(id.into_raw_parts().0)))
+    }
+
+    /// Calls `f` for every entity, to update properties, which every entity has.
+    ///
+    /// The bitsets are not involved at all: the arena is walked directly, like `iter_all_mut`.
+    /// This is the fastest way to go through the whole list, see the `property_update` benchmark:
+    /// `iter_mut::<()>()` visits the same entities, but has to walk a bitset matching every index
+    /// on top of the arena.
+    pub fn for_each_property_mut(&mut self, mut f: impl FnMut(EntityId, &mut E)) {
+        for (id, entity) in self.iter_all_mut() {
+            f(id, entity);
+        }
     }
 
+    /// Shorter spelling of `iter_for_components`, kept so existing code keeps working. Prefer
+    /// `iter_for_components` in new code, `iter` may be deprecated in a future version.
     pub fn iter<'a, C: MultiComponent<'a, E>>(&'a self) -> MultiComponentIter<'a, E, C::BitSet> {
-        C::iter(&self.bitsets, &self.entities)
+        MultiComponentIter {
+            pooled: &self.pooled,
+            ..C::iter(&self.bitsets, &self.entities)
+        }
     }
 
+    /// Shorter spelling of `iter_for_components_mut`, see `iter`.
     pub fn iter_mut<'a, C: MultiComponent<'a, E>>(&'a mut self) -> MultiComponentIterMut<'a, E, C::BitSet> {
-        C::iter_mut(&self.bitsets, &mut self.entities)
+        MultiComponentIterMut {
+            pooled: &self.pooled,
+            ..C::iter_mut(&self.bitsets, &mut self.entities)
+        }
+    }
+
+    #[inline]
+    /// Iterates over the entities having every component of the tuple `C`, for instance
+    /// `iter_for_components::<(A, B)>()`. Unlike `iter_all`, entities missing one of the
+    /// components are skipped.
+    pub fn iter_for_components<'a, C: MultiComponent<'a, E>>(&'a self) -> MultiComponentIter<'a, E, C::BitSet> {
+        self.iter::<C>()
+    }
+
+    #[inline]
+    /// Same as `iter_for_components`, but yields the entities mutably.
+    ///
+    /// Components must not be added or removed during the iteration, otherwise the bitsets would
+    /// be out of date, see `iter_mut_safe` for that.
+    pub fn iter_for_components_mut<'a, C: MultiComponent<'a, E>>(&'a mut self) -> MultiComponentIterMut<'a, E, C::BitSet> {
+        self.iter_mut::<C>()
+    }
+
+    /// Same as `iter`, but only yields the entities whose index (see `Index::into_raw_parts`) is
+    /// in `[start, end)`.
+    ///
+    /// Useful to split the work of a query across threads or frames, see `index_shards`. Bits
+    /// before `start` are still visited in the bitsets, but the entities are not.
+    pub fn iter_range<'a, C: MultiComponent<'a, E>>(&'a self, start: u32, end: u32) -> impl Iterator<Item=(EntityId, &'a E)> {
+        let entities = &self.entities;
+        let pooled = &self.pooled;
+        let end = if C::EVERY_INDEX { end.min(entities.capacity().min(u32::MAX as usize) as u32) } else { end };
+        C::bitset(&self.bitsets).iter()
+            .skip_while(move |index| *index < start)
+            .take_while(move |index| *index < end)
+            .filter_map(move |index| entity_at(entities, index, C::EVERY_INDEX, pooled, C::query_name))
+    }
+
+    /// Iterates over the entities having the component `C`, and only yields those whose `C`
+    /// matches the predicate.
+    ///
+    /// Only the entities having `C` are visited thanks to the bitsets, for instance
+    /// `list.iter_filter(|p: &Position| p.distance(target) < range)`.
+    pub fn iter_filter<'a, C: Component<E>>(&'a self, pred: impl Fn(&C) -> bool + 'a) -> impl Iterator<Item=(EntityId, &'a E)> {
+        self.iter::<(C,)>()
+            .filter(move |(_id, e)| e.get::<C>().is_some_and(&pred))
+    }
+
+    /// Folds every entity matching the given components into an accumulator, for instance to
+    /// compute the total health or the centroid of the positions of a query.
+    ///
+    /// Same as `iter::<C>().fold(...)` for now, but leaves room for a faster implementation.
+    pub fn fold_query<C: for<'a> MultiComponent<'a, E>, A>(&self, init: A, mut f: impl FnMut(A, EntityId, &E) -> A) -> A {
+        self.iter::<C>().fold(init, |acc, (id, e)| f(acc, id, e))
+    }
+
+    /// Returns the `n`th entity (starting from 0) matching the given components, in the order of
+    /// `iter`, or `None` if less than `n + 1` entities match.
+    ///
+    /// Only the bitsets are walked to skip the first `n` entities, which makes it cheap enough to
+    /// display a single row of a list of matching entities.
+    pub fn nth_matching<'a, C: MultiComponent<'a, E>>(&'a self, n: usize) -> Option<(EntityId, &'a E)> {
+        if C::EVERY_INDEX {
+            // the bitset of `()` holds the free slots as well, so they can't be skipped blindly
+            return self.iter::<C>().nth(n);
+        }
+        C::bitset(&self.bitsets).iter().nth(n).map(|index| {
+            self.entities.get_unknown_gen(index as usize)
+                .map(|(v, i)| (i, v))
+                .unwrap_or_else(|| out_of_date_bitset(C::query_name, index))
+        })
+    }
+
+    /// Returns at most `limit` entities matching the given components, starting from the
+    /// `offset`th one (see `nth_matching`).
+    ///
+    /// The returned page is shorter than `limit` at the end of the results, and empty past them.
+    pub fn page<'a, C: MultiComponent<'a, E>>(&'a self, offset: usize, limit: usize) -> Vec<(EntityId, &'a E)> {
+        let mut iter = self.iter::<C>();
+        if offset > 0 && C::EVERY_INDEX {
+            iter.nth(offset - 1);
+        } else if offset > 0 {
+            // skip in the bitsets only, without looking at the entities
+            iter.iter.nth(offset - 1);
+        }
+        iter.take(limit).collect()
+    }
+
+    /// Same as `iter`, but yields the entities by increasing `priority`.
+    ///
+    /// Entities with the same priority are yielded in the same order as `iter`. The matching
+    /// entities are collected and sorted upfront, so `priority` is called once per entity.
+    pub fn iter_prioritized<'a, C: MultiComponent<'a, E>, F: Fn(&E) -> i32>(&'a self, priority: F) -> impl Iterator<Item=(EntityId, &'a E)> {
+        let mut entities: Vec<(EntityId, &'a E)> = self.iter::<C>().collect();
+        entities.sort_by_cached_key(|(_id, e)| priority(e));
+        entities.into_iter()
+    }
+
+    /// Splits the live entities into `n` contiguous index ranges `[start, end)`, to be used with
+    /// `iter_range`.
+    ///
+    /// Ranges are balanced by number of live entities rather than by raw index space, so every
+    /// range holds the same amount of entities, give or take one. Together they cover every live
+    /// entity, and some ranges may be empty (`start == end`) if there are less than `n` entities.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is 0.
+    pub fn index_shards(&self, n: usize) -> Vec<(u32, u32)> {
+        assert!(n > 0, "cannot split an EntityList into 0 shards");
+        let indices: Vec<u32> = self.iter_all()
+            .map(|(id, _e)| bitset_index(id.into_raw_parts().0))
+            .collect();
+        let end = indices.last().map_or(0, |last| last + 1);
+
+        let mut start = 0;
+        (1..=n).map(|k| {
+            let shard_end = indices.get(k * indices.len() / n).copied().unwrap_or(end);
+            let shard = (start, shard_end);
+            start = shard_end;
+            shard
+        }).collect()
+    }
+
+    /// Same as `iter_mut`, but implemented without any unsafe code.
+    ///
+    /// Instead of jumping from one matching entity to the next via the bitsets, every entity is
+    /// visited and checked against the bitsets. In the benchmarks, this is one to two orders of
+    /// magnitude slower than `iter_mut` when few entities match, so prefer `iter_mut` unless
+    /// you have a reason to avoid unsafe code.
+    pub fn iter_mut_scan<'a, C: MultiComponent<'a, E>>(&'a mut self) -> impl Iterator<Item=(EntityId, &'a mut E)> {
+        let bitset = C::bitset(&self.bitsets);
+        let pooled = &self.pooled;
+        self.entities.iter_mut()
+            .filter(move |(id, _e)| {
+                let index = bitset_index(id.into_raw_parts().0);
+                bitset.contains(index) && !pooled.contains(index)
+            })
+    }
+
+    /// Calls `f` with the component `C` of every entity which has it.
+    ///
+    /// Only the entities with `C` are visited thanks to the bitsets, and the component is given
+    /// directly, so there is no need to `unwrap` it. If changes of `C` are tracked, every visited
+    /// component is marked as changed.
+    pub fn for_each_component_mut<C: Component<E>>(&mut self, mut f: impl FnMut(EntityId, &mut C)) {
+        let mut changed = self.changed.get_mut(&C::INDEX);
+        for (id, entity) in <(C,)>::iter_mut(&self.bitsets, &mut self.entities) {
+            let component = C::get_mut(entity)
+                .unwrap_or_else(|| panic!("!!!!FATAL: bitset of ({}) is out of date, entity {:?} does not have this component!!!!", <(C,)>::query_name(), id));
+            f(id, component);
+            if let Some(changed) = changed.as_mut() {
+                changed.add(bitset_index(id.into_raw_parts().0), self.tick);
+            }
+        }
+    }
+
+    /// Calls `f` once for every unordered pair of distinct entities having the component `C`.
+    ///
+    /// Within a pair, the entity with the lowest index comes first. With `n` entities having `C`,
+    /// `f` is called `n * (n - 1) / 2` times, so this is `O(n²)`: for large amounts of entities,
+    /// prefer a spatial structure to only visit the pairs which may interact.
+    pub fn for_each_pair<C: Component<E>>(&self, mut f: impl FnMut((EntityId, &C), (EntityId, &C))) {
+        let components: Vec<(EntityId, &C)> = self.iter::<(C,)>()
+            .filter_map(|(id, e)| C::get(e).map(|c| (id, c)))
+            .collect();
+        for (i, first) in components.iter().enumerate() {
+            for second in &components[i + 1..] {
+                f(*first, *second);
+            }
+        }
+    }
+
+    /// Calls `f` once for every unordered pair of distinct entities having the component `C`, with
+    /// both entities given mutably, for instance to resolve a collision between the two.
+    ///
+    /// The same ordering and `O(n²)` cost as `for_each_pair` apply. The same warning as `get_mut`
+    /// applies as well: do not add or remove components via the given references.
+    pub fn for_each_pair_mut<C: Component<E>>(&mut self, mut f: impl FnMut(EntityId, &mut E, EntityId, &mut E)) {
+        let ids: Vec<EntityId> = self.iter::<(C,)>().map(|(id, _e)| id).collect();
+        for (i, first_id) in ids.iter().enumerate() {
+            for second_id in &ids[i + 1..] {
+                if let (Some(first), Some(second)) = self.entities.get2_mut(*first_id, *second_id) {
+                    f(*first_id, first, *second_id, second);
+                }
+            }
+        }
     }
 }
 
 pub struct MultiComponentIter<'a, E: EntityBase, B: BitSetLike> {
     pub (crate) iter: BitIter<B>,
     pub (crate) values: &'a Arena<E>,
+    /// Only used to name the components in the panic messages.
+    pub (crate) query_name: fn() -> String,
+    /// See `MultiComponent::EVERY_INDEX`.
+    pub (crate) every_index: bool,
+    /// The pooled entities of the list, skipped along with the free slots when `every_index` is
+    /// set. The other queries can't match them, pooled entities have no components.
+    pub (crate) pooled: &'a BitSet,
 }
 
 impl<'a, E: EntityBase, B: BitSetLike> MultiComponentIter<'a, E, B> {
@@ -35,6 +250,9 @@ impl<'a, E: EntityBase, B: BitSetLike> MultiComponentIter<'a, E, B> {
         MultiComponentIter {
             iter,
             values,
+            query_name: unknown_query_name,
+            every_index: false,
+            pooled: empty_bitset(),
         }
     }
 }
@@ -42,8 +260,14 @@ impl<'a, E: EntityBase, B: BitSetLike> MultiComponentIter<'a, E, B> {
 pub struct MultiComponentIterMut<'a, E: EntityBase, B: BitSetLike> {
     pub (crate) iter: BitIter<B>,
     pub (crate) values: &'a mut Arena<E>,
-    #[cfg(debug_assertions)]
-    pub (crate) n: Option<usize>,
+    pub (crate) last_index: Option<u32>,
+    /// Only used to name the components in the panic messages.
+    pub (crate) query_name: fn() -> String,
+    /// See `MultiComponent::EVERY_INDEX`.
+    pub (crate) every_index: bool,
+    /// The pooled entities of the list, skipped along with the free slots when `every_index` is
+    /// set. The other queries can't match them, pooled entities have no components.
+    pub (crate) pooled: &'a BitSet,
 }
 
 impl<'a, E: EntityBase, B: BitSetLike> MultiComponentIterMut<'a, E, B> {
@@ -51,22 +275,93 @@ impl<'a, E: EntityBase, B: BitSetLike> MultiComponentIterMut<'a, E, B> {
         MultiComponentIterMut {
             iter,
             values,
-            #[cfg(debug_assertions)]
-            n: None,
+            last_index: None,
+            query_name: unknown_query_name,
+            every_index: false,
+            pooled: empty_bitset(),
         }
     }
 }
 
+impl<'a, E: EntityBase, B: BitSetLike + Clone> MultiComponentIter<'a, E, B> {
+    /// Returns the number of entities this iterator has yet to yield.
+    ///
+    /// This counts the remaining bits of the bitsets, without visiting the entities, so it costs
+    /// about as much as going through the rest of the bitsets. Meant for progress reporting,
+    /// not to be called at every step.
+    pub fn remaining(&self) -> usize {
+        remaining(self.iter.clone(), self.values, self.every_index, self.pooled)
+    }
+}
+
+impl<'a, E: EntityBase, B: BitSetLike + Clone> MultiComponentIterMut<'a, E, B> {
+    /// Returns the number of entities this iterator has yet to yield.
+    ///
+    /// See `MultiComponentIter::remaining`.
+    pub fn remaining(&self) -> usize {
+        remaining(self.iter.clone(), self.values, self.every_index, self.pooled)
+    }
+}
+
+/// The bitset of the components whose bitset was not created yet.
+fn empty_bitset() -> &'static BitSet {
+    static EMPTY: OnceLock<BitSet> = OnceLock::new();
+    EMPTY.get_or_init(BitSet::new)
+}
+
+/// Panics because the bitset of the query `query_name` returned the index of a free slot.
+///
+/// Kept out of line, so that the iterators calling it stay small enough to be inlined.
+#[cold]
+#[inline(never)]
+pub (crate) fn out_of_date_bitset(query_name: fn() -> String, index: u32) -> ! {
+    panic!("!!!!FATAL: bitset of ({}) is out of date, it returned index {}, but no entity exists at this location!!!!\n\
+        Check that your code adds components and entities via the legal methods!", query_name(), index)
+}
+
+/// Looks up the entity at an index returned by the bitset of a query.
+///
+/// Free slots and pooled entities are skipped for `()`, which matches every index (see
+/// `MultiComponent::EVERY_INDEX`), but free slots mean that the bitset is out of date for any other
+/// query.
+#[inline]
+pub (crate) fn entity_at<'a, E>(entities: &'a Arena<E>, index: u32, every_index: bool, pooled: &BitSet, query_name: fn() -> String) -> Option<(EntityId, &'a E)> {
+    match entities.get_unknown_gen(index as usize) {
+        Some(_) if every_index && pooled.contains(index) => None,
+        Some((e, id)) => Some((id, e)),
+        None if every_index => None,
+        None => out_of_date_bitset(query_name, index),
+    }
+}
+
+fn remaining<E, B: BitSetLike>(iter: BitIter<B>, values: &Arena<E>, every_index: bool, pooled: &BitSet) -> usize {
+    if every_index {
+        iter.take_while(|index| (*index as usize) < values.capacity())
+            .filter(|index| !pooled.contains(*index) && values.get_unknown_gen(*index as usize).is_some())
+            .count()
+    } else {
+        iter.count()
+    }
+}
+
+fn unknown_query_name() -> String {
+    String::from("<unknown components>")
+}
+
 impl<'a, E: EntityBase, B: BitSetLike> Iterator for MultiComponentIter<'a, E, B> {
     type Item = (EntityId, &'a E);
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.iter.next().map(|index| {
-            self.values.get_unknown_gen(index as usize)
-                .map(|(v, i)| (i, v))
-                .expect("!!!!FATAL: bitset is out of date, bitset returned true for an entity, but no entity exists at this location!!!!\n\
-                        Check that your code adds components and entities via the legal methods!")
-        })
+        loop {
+            let index = self.iter.next()?;
+            if self.every_index && index as usize >= self.values.capacity() {
+                // `()` matches every index, but no entity lives past the last slot of the arena
+                return None;
+            }
+            if let Some(item) = entity_at(self.values, index, self.every_index, self.pooled, self.query_name) {
+                return Some(item);
+            }
+        }
     }
 }
 
@@ -74,97 +369,161 @@ impl<'a, E: EntityBase, B: BitSetLike> Iterator for MultiComponentIterMut<'a, E,
     type Item = (EntityId, &'a mut E);
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.iter.next().map(|index| {
-            let (v, id) = self.values.get_unknown_gen_mut(index as usize)
-                .expect("!!!!FATAL: bitset is out of date, bitset returned true for an entity, but no entity exists at this location!!!!\n\
-                        Check that your code adds components and entities via the legal methods!");
-        
-            #[cfg(debug_assertions)] {
-                // check that n is strictly monotonic increasing,
-                // meaning that the same value will never be indexed twice,
-                // THEREFORE we can safely allow the unsafe code below, that unlinks
-                // the lifetime of the source with the lifetime of the Iterator::Item
-                // we still cannot make the items of the iterator outlive the source,
-                // nor can we mutate the source object, but at least we can call .next() safely.
-                let index = id.into_raw_parts().0;
-                if let Some(old_n) = self.n {
-                    debug_assert!(old_n < index);
-                }
-                self.n = Some(index);
+        loop {
+            let index = self.iter.next()?;
+            // check that the index is strictly monotonic increasing,
+            // meaning that the same value will never be indexed twice,
+            // THEREFORE we can safely allow the unsafe code below, that unlinks
+            // the lifetime of the source with the lifetime of the Iterator::Item
+            // we still cannot make the items of the iterator outlive the source,
+            // nor can we mutate the source object, but at least we can call .next() safely.
+            //
+            // This is checked in release mode as well: it only costs a comparison, and a faulty
+            // bitset must result in a panic rather than in two aliasing mutable references.
+            if let Some(last_index) = self.last_index {
+                assert!(last_index < index, "!!!!FATAL: bitset of ({}) yielded index {} after index {}, \
+                    this would give out the same entity mutably twice!!!!", (self.query_name)(), index, last_index);
+            }
+            self.last_index = Some(index);
+
+            let every_index = self.every_index;
+            if every_index && index as usize >= self.values.capacity() {
+                // see `MultiComponentIter::next`
+                return None;
+            }
+            if every_index && self.pooled.contains(index) {
+                continue;
             }
-            
+            let query_name = self.query_name;
+            let (v, id) = match self.values.get_unknown_gen_mut(index as usize) {
+                Some(entry) => entry,
+                None if every_index => continue,
+                None => out_of_date_bitset(query_name, index),
+            };
+
             #[allow(unsafe_code)]
-            (id, unsafe { &mut *(v as *mut _) }) 
-        })
+            return Some((id, unsafe { &mut *(v as *mut _) }));
+        }
     }
 }
 
 /// Trait used internally, implemented for every tuple of component.
 ///
+/// Tuples of up to 24 components are supported. This only limits how many components a single
+/// query may ask for, an entity itself may have any number of components.
+///
 /// Do not implement externally.
 pub trait MultiComponent<'a, E: EntityBase> {
     type BitSet: BitSetLike;
 
-    fn bitset(bitsets: &'a HashMap<TypeId, BitSet>) -> Self::BitSet;
+    /// True if `bitset` holds every index, including the free slots of the arena, instead of the
+    /// entities matching the query. Only `()` does: the free slots are then skipped, instead of
+    /// being reported as an out of date bitset.
+    const EVERY_INDEX: bool = false;
 
-    fn iter(bitsets: &'a HashMap<TypeId, BitSet>, arena: &'a Arena<E>) -> MultiComponentIter<'a, E, Self::BitSet> {
-        MultiComponentIter::new(Self::bitset(bitsets).iter(), arena)
+    fn bitset(bitsets: &'a [Option<BitSet>]) -> Self::BitSet;
+
+    /// The names of the components of this query, separated by commas, as given by
+    /// `EntityBase::component_name`.
+    fn query_name() -> String;
+
+    fn iter(bitsets: &'a [Option<BitSet>], arena: &'a Arena<E>) -> MultiComponentIter<'a, E, Self::BitSet> {
+        MultiComponentIter {
+            query_name: Self::query_name,
+            every_index: Self::EVERY_INDEX,
+            ..MultiComponentIter::new(Self::bitset(bitsets).iter(), arena)
+        }
     }
 
-    fn iter_mut(bitsets: &'a HashMap<TypeId, BitSet>, arena: &'a mut Arena<E>) -> MultiComponentIterMut<'a, E, Self::BitSet> {
-        MultiComponentIterMut::new(Self::bitset(bitsets).iter(), arena)
+    fn iter_mut(bitsets: &'a [Option<BitSet>], arena: &'a mut Arena<E>) -> MultiComponentIterMut<'a, E, Self::BitSet> {
+        MultiComponentIterMut {
+            query_name: Self::query_name,
+            every_index: Self::EVERY_INDEX,
+            ..MultiComponentIterMut::new(Self::bitset(bitsets).iter(), arena)
+        }
     }
 }
 
 impl<'a, E: EntityBase> MultiComponent<'a, E> for () {
     type BitSet = BitSetAll;
 
-    fn bitset(_bitsets: &'a HashMap<TypeId, BitSet>) -> Self::BitSet {
+    const EVERY_INDEX: bool = true;
+
+    fn bitset(_bitsets: &'a [Option<BitSet>]) -> Self::BitSet {
         BitSetAll
     }
+
+    fn query_name() -> String {
+        String::new()
+    }
 }
 
 impl<'a, E: EntityBase, C: Component<E>> MultiComponent<'a, E> for (C,) {
     type BitSet = &'a BitSet;
 
-    fn bitset(bitsets: &'a HashMap<TypeId, BitSet>) -> Self::BitSet {
-        bitsets.get(&TypeId::of::<C>()).expect("FATAL: bitset is non-existant for composant")
+    fn bitset(bitsets: &'a [Option<BitSet>]) -> Self::BitSet {
+        // with lazy bitsets (see `EntityListConfig`), the bitset of a component no entity ever had
+        // does not exist yet.
+        match get_bitset(bitsets, C::INDEX) {
+            Some(bitset) => bitset,
+            None => empty_bitset(),
+        }
+    }
+
+    fn query_name() -> String {
+        String::from(E::component_name(C::INDEX).unwrap_or_else(std::any::type_name::<C>))
     }
 }
 
 macro_rules! multi_component_impl {
-    // use variables to indicate the arity of the tuple
-    ($($ty:ident),*) => {
-        impl<'a, E: EntityBase, $($ty: Component<E>),*> MultiComponent<'a, E> for ($($ty),*)
+    // the tuple is split in two halves, whose bitsets are joined together. The right half holds
+    // the extra component when the arity is odd.
+    ([$($left:ident),*], [$($right:ident),*]) => {
+        impl<'a, E: EntityBase, $($left: Component<E>,)* $($right: Component<E>),*> MultiComponent<'a, E> for ($($left,)* $($right,)*)
         {
             type BitSet = BitSetAnd<
-                <<Self as Split>::Left as MultiComponent<'a, E>>::BitSet,
-                <<Self as Split>::Right as MultiComponent<'a, E>>::BitSet
+                <($($left,)*) as MultiComponent<'a, E>>::BitSet,
+                <($($right,)*) as MultiComponent<'a, E>>::BitSet
             >;
 
-            fn bitset(bitsets: &'a HashMap<TypeId, BitSet>) -> Self::BitSet {
+            fn bitset(bitsets: &'a [Option<BitSet>]) -> Self::BitSet {
                 let (l, r) = (
-                    <<Self as Split>::Left as MultiComponent<'a, E>>::bitset(bitsets),
-                    <<Self as Split>::Right as MultiComponent<'a, E>>::bitset(bitsets)
+                    <($($left,)*) as MultiComponent<'a, E>>::bitset(bitsets),
+                    <($($right,)*) as MultiComponent<'a, E>>::bitset(bitsets)
                 );
                 BitSetAnd(l, r)
             }
+
+            fn query_name() -> String {
+                format!("{}, {}",
+                    <($($left,)*) as MultiComponent<'a, E>>::query_name(),
+                    <($($right,)*) as MultiComponent<'a, E>>::query_name()
+                )
+            }
         }
     }
 }
 
-multi_component_impl!(C1, C2);
-multi_component_impl!(C1, C2, C3);
-multi_component_impl!(C1, C2, C3, C4);
-multi_component_impl!(C1, C2, C3, C4, C5);
-multi_component_impl!(C1, C2, C3, C4, C5, C6);
-multi_component_impl!(C1, C2, C3, C4, C5, C6, C7);
-multi_component_impl!(C1, C2, C3, C4, C5, C6, C7, C8);
-multi_component_impl!(C1, C2, C3, C4, C5, C6, C7, C8, C9);
-multi_component_impl!(C1, C2, C3, C4, C5, C6, C7, C8, C9, C10);
-multi_component_impl!(C1, C2, C3, C4, C5, C6, C7, C8, C9, C10, C11);
-multi_component_impl!(C1, C2, C3, C4, C5, C6, C7, C8, C9, C10, C11, C12);
-multi_component_impl!(C1, C2, C3, C4, C5, C6, C7, C8, C9, C10, C11, C12, C13);
-multi_component_impl!(C1, C2, C3, C4, C5, C6, C7, C8, C9, C10, C11, C12, C13, C14);
-multi_component_impl!(C1, C2, C3, C4, C5, C6, C7, C8, C9, C10, C11, C12, C13, C14, C15);
-multi_component_impl!(C1, C2, C3, C4, C5, C6, C7, C8, C9, C10, C11, C12, C13, C14, C15, C16);
\ No newline at end of file
+multi_component_impl!([C1], [C2]);
+multi_component_impl!([C1], [C2, C3]);
+multi_component_impl!([C1, C2], [C3, C4]);
+multi_component_impl!([C1, C2], [C3, C4, C5]);
+multi_component_impl!([C1, C2, C3], [C4, C5, C6]);
+multi_component_impl!([C1, C2, C3], [C4, C5, C6, C7]);
+multi_component_impl!([C1, C2, C3, C4], [C5, C6, C7, C8]);
+multi_component_impl!([C1, C2, C3, C4], [C5, C6, C7, C8, C9]);
+multi_component_impl!([C1, C2, C3, C4, C5], [C6, C7, C8, C9, C10]);
+multi_component_impl!([C1, C2, C3, C4, C5], [C6, C7, C8, C9, C10, C11]);
+multi_component_impl!([C1, C2, C3, C4, C5, C6], [C7, C8, C9, C10, C11, C12]);
+multi_component_impl!([C1, C2, C3, C4, C5, C6], [C7, C8, C9, C10, C11, C12, C13]);
+multi_component_impl!([C1, C2, C3, C4, C5, C6, C7], [C8, C9, C10, C11, C12, C13, C14]);
+multi_component_impl!([C1, C2, C3, C4, C5, C6, C7], [C8, C9, C10, C11, C12, C13, C14, C15]);
+multi_component_impl!([C1, C2, C3, C4, C5, C6, C7, C8], [C9, C10, C11, C12, C13, C14, C15, C16]);
+multi_component_impl!([C1, C2, C3, C4, C5, C6, C7, C8], [C9, C10, C11, C12, C13, C14, C15, C16, C17]);
+multi_component_impl!([C1, C2, C3, C4, C5, C6, C7, C8, C9], [C10, C11, C12, C13, C14, C15, C16, C17, C18]);
+multi_component_impl!([C1, C2, C3, C4, C5, C6, C7, C8, C9], [C10, C11, C12, C13, C14, C15, C16, C17, C18, C19]);
+multi_component_impl!([C1, C2, C3, C4, C5, C6, C7, C8, C9, C10], [C11, C12, C13, C14, C15, C16, C17, C18, C19, C20]);
+multi_component_impl!([C1, C2, C3, C4, C5, C6, C7, C8, C9, C10], [C11, C12, C13, C14, C15, C16, C17, C18, C19, C20, C21]);
+multi_component_impl!([C1, C2, C3, C4, C5, C6, C7, C8, C9, C10, C11], [C12, C13, C14, C15, C16, C17, C18, C19, C20, C21, C22]);
+multi_component_impl!([C1, C2, C3, C4, C5, C6, C7, C8, C9, C10, C11], [C12, C13, C14, C15, C16, C17, C18, C19, C20, C21, C22, C23]);
+multi_component_impl!([C1, C2, C3, C4, C5, C6, C7, C8, C9, C10, C11, C12], [C13, C14, C15, C16, C17, C18, C19, C20, C21, C22, C23, C24]);
\ No newline at end of file
@@ -24,6 +24,25 @@
 //! and components also implement `Serialize` and `Deserialize`, or you can manually implement
 //! both those traits for your entity.
 //!
+//! Ids are preserved by the serialization, so `EntityId`s stored elsewhere (for instance as keys of
+//! your own maps, serialized alongside the list) still refer to the same entities once deserialized.
+//!
+//! ## Reflection
+//!
+//! With the feature `reflect` of this crate, entities defined with `#[reflect]` (see
+//! [`define_entity`]) implement `ReflectEntity`, and their components can be read as
+//! `bevy_reflect::Reflect` via `EntityList::reflect_component`.
+//!
+//! ## Parallel iteration
+//!
+//! With the feature `rayon` of this crate, `EntityList::par_iter` iterates through the entities
+//! matching some components in parallel, as long as your entity is `Sync`.
+//!
+//! ## Random sampling
+//!
+//! With the feature `rand`, `EntityList::random_matching` picks a random entity matching some
+//! components.
+//!
 //! ## Components vs Properties
 //!
 //! Components are **optional** properties that you can add and remove at runtime. From a gamedev
@@ -41,15 +60,15 @@
 //! ```rust
 //! use mobec::{define_entity, EntityList, EntityBase};
 //! 
-//! #[derive(Debug)]
+//! #[derive(Debug, Clone)]
 //! pub struct A {
 //!     n: i32
 //! }
-//! #[derive(Debug)]
+//! #[derive(Debug, Clone)]
 //! pub struct B {
 //!     b: String
 //! }
-//! #[derive(Debug)]
+//! #[derive(Debug, Clone)]
 //! pub struct C {
 //!     c: bool
 //! }
@@ -136,10 +155,44 @@
 
 mod entity;
 mod entity_list;
+mod config;
+mod changes;
+mod stats;
+mod weak;
+mod tags;
+mod query;
+mod edit;
+mod spatial;
+mod pool;
+mod mask;
+mod view;
 pub mod iter;
 
 #[cfg(feature = "use_serde")]
 mod serde;
 
+#[cfg(feature = "rayon")]
+mod par;
+
+#[cfg(feature = "rand")]
+mod random;
+
+#[cfg(feature = "reflect")]
+mod reflect;
+#[cfg(feature = "reflect")]
+pub use reflect::*;
+#[cfg(feature = "reflect")]
+#[doc(hidden)]
+pub use bevy_reflect;
+
 pub use entity::*;
-pub use entity_list::*;
\ No newline at end of file
+pub use entity_list::*;
+pub use config::*;
+pub use changes::*;
+pub use stats::*;
+pub use weak::*;
+pub use query::*;
+pub use edit::*;
+pub use spatial::*;
+pub use mask::*;
+pub use view::*;
\ No newline at end of file
@@ -0,0 +1,123 @@
+use std::marker::PhantomData;
+
+use crate::{Component, EntityBase};
+
+/// A set of components of the entity `E`, for instance the components an entity should have
+/// once it is complete.
+///
+/// Built with `ComponentMask::new().with::<A>().with::<B>()`, or from an entity with
+/// `EntityBase::component_mask`.
+pub struct ComponentMask<E: EntityBase> {
+    // one bit per `Component::INDEX`, without trailing zero words so that equal masks compare equal.
+    words: Vec<u64>,
+    _marker: PhantomData<fn() -> E>,
+}
+
+impl<E: EntityBase> ComponentMask<E> {
+    /// Creates an empty mask.
+    pub fn new() -> ComponentMask<E> {
+        ComponentMask {
+            words: Vec::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    #[inline]
+    /// Returns this mask with the component `C` added.
+    pub fn with<C: Component<E>>(mut self) -> Self {
+        self.insert_index(C::INDEX);
+        self
+    }
+
+    #[inline]
+    /// Adds the component `C` to this mask.
+    pub fn insert<C: Component<E>>(&mut self) {
+        self.insert_index(C::INDEX);
+    }
+
+    #[inline]
+    /// Returns true if the component `C` is in this mask.
+    pub fn contains<C: Component<E>>(&self) -> bool {
+        self.contains_index(C::INDEX)
+    }
+
+    /// Adds the component with the given index (see `Component::INDEX`) to this mask.
+    pub fn insert_index(&mut self, component_index: usize) {
+        let word = component_index / 64;
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+        self.words[word] |= 1 << (component_index % 64);
+    }
+
+    /// Returns true if the component with the given index (see `Component::INDEX`) is in this mask.
+    pub fn contains_index(&self, component_index: usize) -> bool {
+        self.words.get(component_index / 64)
+            .is_some_and(|word| word & (1 << (component_index % 64)) != 0)
+    }
+
+    /// Returns the number of components in this mask.
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// Returns true if this mask has no components.
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Returns the components in this mask which are not in `other`.
+    pub fn difference(&self, other: &ComponentMask<E>) -> ComponentMask<E> {
+        let mut words: Vec<u64> = self.words.iter()
+            .enumerate()
+            .map(|(i, word)| word & !other.words.get(i).copied().unwrap_or(0))
+            .collect();
+        while words.last() == Some(&0) {
+            words.pop();
+        }
+        ComponentMask {
+            words,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Iterates over the indices (see `Component::INDEX`) of the components in this mask, in
+    /// ascending order.
+    pub fn iter(&self) -> impl Iterator<Item=usize> + '_ {
+        self.words.iter()
+            .enumerate()
+            .flat_map(|(i, word)| (0..64).filter(move |bit| word & (1 << bit) != 0).map(move |bit| i * 64 + bit))
+    }
+}
+
+impl<E: EntityBase> Default for ComponentMask<E> {
+    fn default() -> ComponentMask<E> {
+        ComponentMask::new()
+    }
+}
+
+impl<E: EntityBase> Clone for ComponentMask<E> {
+    fn clone(&self) -> ComponentMask<E> {
+        ComponentMask {
+            words: self.words.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<E: EntityBase> PartialEq for ComponentMask<E> {
+    fn eq(&self, other: &ComponentMask<E>) -> bool {
+        self.words == other.words
+    }
+}
+
+impl<E: EntityBase> Eq for ComponentMask<E> {}
+
+impl<E: EntityBase> std::fmt::Debug for ComponentMask<E> {
+    /// Shows the field names of the components, see `EntityBase::component_name`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set()
+            .entries(self.iter().map(|component_index| E::component_name(component_index).unwrap_or("?")))
+            .finish()
+    }
+}
\ No newline at end of file
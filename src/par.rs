@@ -0,0 +1,47 @@
+use hibitset::BitSetLike;
+use rayon::iter::{Either, IntoParallelIterator, ParallelIterator};
+
+use crate::{EntityBase, EntityId, EntityList};
+use crate::iter::{MultiComponent, entity_at};
+
+impl<E: EntityBase + Sync> EntityList<E> {
+    /// Same as `iter`, but yields the entities in parallel with rayon.
+    ///
+    /// The matching bits of the bitsets are split across the threads of the rayon pool, and the
+    /// entities are only read, so this is safe to combine with any other read-only access, for
+    /// instance `list.par_iter::<(Position,)>().filter(...).count()`.
+    pub fn par_iter<'a, C: MultiComponent<'a, E>>(&'a self) -> impl ParallelIterator<Item=(EntityId, &'a E)> + 'a
+    where C::BitSet: Send + Sync + 'a {
+        let entities = &self.entities;
+        let pooled = &self.pooled;
+        if C::EVERY_INDEX {
+            // the bitset of `()` holds every index, only the slots of the arena are worth splitting
+            Either::Left((0..entities.capacity().min(u32::MAX as usize) as u32).into_par_iter()
+                .filter_map(move |index| entity_at(entities, index, true, pooled, C::query_name)))
+        } else {
+            Either::Right(C::bitset(&self.bitsets).par_iter()
+                .filter_map(move |index| entity_at(entities, index, false, pooled, C::query_name)))
+        }
+    }
+
+    /// Runs `f` in parallel on every entity matching the given components, and writes the
+    /// returned values in `out` at the returned indices.
+    ///
+    /// Entities for which `f` returns `None` leave `out` untouched. The values are computed in
+    /// parallel, but written afterwards from the current thread, so `f` never has to touch `out`
+    /// itself. If several entities return the same index, the entity with the highest index
+    /// (see `Index::into_raw_parts`) wins.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `f` returns an index out of bounds of `out`.
+    pub fn compute_into<'a, C: MultiComponent<'a, E>, T: Send>(&'a self, out: &mut [T], f: impl Fn(EntityId, &E) -> Option<(usize, T)> + Sync)
+    where C::BitSet: Send + Sync + 'a {
+        let results: Vec<(usize, T)> = self.par_iter::<C>()
+            .filter_map(|(id, e)| f(id, e))
+            .collect();
+        for (index, value) in results {
+            out[index] = value;
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,54 @@
+use crate::{EntityBase, EntityId, EntityList};
+use crate::entity_list::bitset_index;
+
+impl<E: EntityBase> EntityList<E> {
+    /// Removes all the components of the given entity, and keeps it aside to be reused by
+    /// `spawn_from_pool`.
+    ///
+    /// **The id of the entity is handed out again by `spawn_from_pool`**: unlike with `remove`,
+    /// an old copy of the id does not become invalid, it silently refers to the next entity
+    /// spawned from the pool. Forget every copy of the id before pooling the entity.
+    ///
+    /// The slot of the entity stays occupied, so it is not reused by `insert` and the generation
+    /// of the list does not move. Pooled entities have no components, so they are skipped by
+    /// queries, `()` included, and they are skipped by `iter_all`, `len`, `retain` and the other
+    /// whole-list operations as well. `get` still returns them, but `replace`, `swap`,
+    /// `add_component_for_entity`, `apply_component_updates` and `get_or_insert_with` treat them
+    /// as missing, see `is_pooled`. Cloning keeps them pooled, but `partition` drops them, and
+    /// after a serde round-trip they are regular entities without components.
+    ///
+    /// Does nothing if the entity does not exist anymore or is already pooled.
+    pub fn despawn_to_pool(&mut self, id: EntityId) {
+        if self.entities.contains(id) && !self.pooled.add(bitset_index(id.into_raw_parts().0)) {
+            self.recycle(id);
+            self.pool.push(id);
+        }
+    }
+
+    /// Creates an entity with the given properties in the slot of a pooled entity (see
+    /// `despawn_to_pool`), or inserts a new one if the pool is empty.
+    pub fn spawn_from_pool(&mut self, props: E::CreationParams) -> EntityId {
+        match self.pool.pop() {
+            Some(id) => {
+                self.pooled.remove(bitset_index(id.into_raw_parts().0));
+                self.replace(id, E::new(props));
+                id
+            },
+            None => self.insert(E::new(props)),
+        }
+    }
+
+    #[inline]
+    /// Returns true if the entity exists and waits in the pool, see `despawn_to_pool`.
+    ///
+    /// Pooled entities can't be given components or be replaced until they are spawned again:
+    /// `spawn_from_pool` would silently wipe them.
+    pub fn is_pooled(&self, id: EntityId) -> bool {
+        self.entities.contains(id) && self.pooled.contains(bitset_index(id.into_raw_parts().0))
+    }
+
+    /// Returns the number of entities waiting in the pool.
+    pub fn pool_len(&self) -> usize {
+        self.pool.len()
+    }
+}
\ No newline at end of file
@@ -0,0 +1,81 @@
+use hibitset::{BitSet, BitSetLike, BitSetNot};
+
+use crate::{Component, EntityBase, EntityList};
+use crate::iter::{MultiComponent, MultiComponentIter};
+use crate::entity_list::bitset_index;
+
+/// A query built at runtime, returned by `EntityList::query`.
+///
+/// Filters are applied from left to right: `list.query().with::<A>().without::<B>().or_with::<C>()`
+/// matches the entities with `(A and not B) or C`. Without any filter, every entity matches.
+///
+/// Unlike `EntityList::iter`, the resulting bitset is materialized as a new `BitSet` at every step,
+/// so prefer static queries when the components are known at compile time.
+pub struct QueryBuilder<'a, E: EntityBase> {
+    list: &'a EntityList<E>,
+    // None means every entity matches.
+    bitset: Option<BitSet>,
+}
+
+impl<E: EntityBase> EntityList<E> {
+    /// Starts a query which can be composed at runtime, see `QueryBuilder`.
+    pub fn query(&self) -> QueryBuilder<'_, E> {
+        QueryBuilder {
+            list: self,
+            bitset: None,
+        }
+    }
+}
+
+impl<'a, E: EntityBase> QueryBuilder<'a, E> {
+    fn component_bitset<C: Component<E>>(&self) -> &'a BitSet {
+        <(C,) as MultiComponent<'a, E>>::bitset(&self.list.bitsets)
+    }
+
+    /// Every entity of the list, the pooled ones excepted (see `EntityList::despawn_to_pool`).
+    fn all_entities(&self) -> BitSet {
+        self.list.iter_all()
+            .map(|(id, _e)| bitset_index(id.into_raw_parts().0))
+            .collect()
+    }
+
+    /// Only keep the entities which have the component `C`.
+    pub fn with<C: Component<E>>(mut self) -> Self {
+        let component_bitset = self.component_bitset::<C>();
+        self.bitset = Some(match self.bitset {
+            None => component_bitset.clone(),
+            Some(mut bitset) => {
+                bitset &= component_bitset;
+                bitset
+            }
+        });
+        self
+    }
+
+    /// Only keep the entities which don't have the component `C`.
+    pub fn without<C: Component<E>>(mut self) -> Self {
+        let component_bitset = self.component_bitset::<C>();
+        let mut bitset = self.bitset.take().unwrap_or_else(|| self.all_entities());
+        bitset &= &BitSetNot(component_bitset);
+        self.bitset = Some(bitset);
+        self
+    }
+
+    /// Also match the entities which have the component `C`, on top of the ones already matching.
+    pub fn or_with<C: Component<E>>(mut self) -> Self {
+        let component_bitset = self.component_bitset::<C>();
+        if let Some(bitset) = self.bitset.as_mut() {
+            *bitset |= component_bitset;
+        }
+        self
+    }
+
+    /// Iterates over the matching entities.
+    pub fn iter(self) -> MultiComponentIter<'a, E, BitSet> {
+        let bitset = match self.bitset {
+            Some(bitset) => bitset,
+            None => self.all_entities(),
+        };
+        MultiComponentIter::new(bitset.iter(), &self.list.entities)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,25 @@
+use hibitset::BitSetLike;
+use rand::Rng;
+
+use crate::{EntityBase, EntityId, EntityList};
+use crate::iter::MultiComponent;
+
+impl<E: EntityBase> EntityList<E> {
+    /// Picks an entity matching the given components uniformly at random, for instance a random
+    /// spawn point with `list.random_matching::<(SpawnPoint,), _>(&mut rng)`.
+    ///
+    /// Returns `None` if no entity matches. The matches are counted via the bitsets first, so
+    /// this walks the bitsets about twice, but only ever looks at a single entity.
+    pub fn random_matching<'a, C: MultiComponent<'a, E>, R: Rng + ?Sized>(&'a self, rng: &mut R) -> Option<(EntityId, &'a E)> {
+        let count = if C::EVERY_INDEX {
+            // the bitset of `()` holds the free slots as well, and the pooled entities are left out
+            self.len()
+        } else {
+            C::bitset(&self.bitsets).iter().count()
+        };
+        if count == 0 {
+            return None;
+        }
+        self.nth_matching::<C>(rng.gen_range(0..count))
+    }
+}
\ No newline at end of file
@@ -0,0 +1,27 @@
+use std::any::TypeId;
+
+use bevy_reflect::Reflect;
+
+use crate::{EntityBase, EntityId, EntityList};
+
+/// Entities whose components can be accessed as `bevy_reflect::Reflect`, for reflection-based
+/// tooling such as editors.
+///
+/// Implemented by `define_entity` for entities marked with `#[reflect]`, do not implement by hand.
+pub trait ReflectEntity: EntityBase {
+    /// Returns the component with the given `TypeId` as `Reflect`, if this entity has it.
+    ///
+    /// Same as `EntityBase::get_component_any`, but the fields of the component can be read
+    /// without knowing its type.
+    fn reflect_component(&self, type_id: TypeId) -> Option<&dyn Reflect>;
+}
+
+impl<E: ReflectEntity> EntityList<E> {
+    #[inline]
+    /// Retrieves the component with the given `TypeId` of an entity, as `Reflect`.
+    ///
+    /// Returns `None` if the entity doesn't exist, or if it doesn't have the component.
+    pub fn reflect_component(&self, id: EntityId, type_id: TypeId) -> Option<&dyn Reflect> {
+        self.entities.get(id).and_then(|e| e.reflect_component(type_id))
+    }
+}
\ No newline at end of file
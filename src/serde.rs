@@ -17,6 +17,8 @@ where
     }
 }
 
+/// Only the entities are deserialized, the list is rebuilt with `EntityList::from_arena`, and so with
+/// the default `EntityListConfig`.
 impl<'de, E> Deserialize<'de> for EntityList<E> where E: Deserialize<'de> + EntityBase {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -0,0 +1,38 @@
+use crate::{EntityBase, EntityId, EntityList};
+use crate::iter::MultiComponent;
+
+/// A spatial structure (grid, quadtree, ...) which can be rebuilt from an `EntityList`.
+///
+/// This crate doesn't provide any spatial structure, this trait is only the glue between
+/// `EntityList::rebuild_spatial` and your own structure, or one from another crate.
+pub trait SpatialIndex<E: EntityBase> {
+    /// Removes every entity from the index. Called once before every rebuild.
+    fn clear(&mut self);
+
+    /// Adds an entity to the index.
+    ///
+    /// Entities which have no position (for instance, no `Position` component) can simply be
+    /// ignored.
+    fn insert(&mut self, id: EntityId, entity: &E);
+}
+
+impl<E: EntityBase> EntityList<E> {
+    /// Clears the given spatial index, and feeds it every entity of the list.
+    pub fn rebuild_spatial<S: SpatialIndex<E>>(&self, index: &mut S) {
+        index.clear();
+        for (id, entity) in self.iter_all() {
+            index.insert(id, entity);
+        }
+    }
+
+    /// Clears the given spatial index, and feeds it the entities matching the given components
+    /// only, typically the ones with a position.
+    ///
+    /// Only the matching entities are visited thanks to the bitsets.
+    pub fn rebuild_spatial_with<C: for<'a> MultiComponent<'a, E>, S: SpatialIndex<E>>(&self, index: &mut S) {
+        index.clear();
+        for (id, entity) in self.iter::<C>() {
+            index.insert(id, entity);
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,146 @@
+use std::any::TypeId;
+use std::collections::BTreeMap;
+
+use hashbrown::HashSet;
+use hibitset::BitSet;
+
+use crate::{EntityBase, EntityList};
+use crate::changes::ChangedSet;
+use crate::entity_list::get_bitset;
+
+/// A summary of how components are distributed in an `EntityList`.
+///
+/// Returned by `EntityList::stats`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntityListStats {
+    /// Total number of entities in the list.
+    pub entity_count: usize,
+    /// Number of entities having each component, indexed by the field name of the component (see
+    /// `EntityBase::component_name`).
+    pub component_counts: BTreeMap<&'static str, usize>,
+    /// Number of distinct sets of active components found across all entities.
+    ///
+    /// A low number means entities are similar to each other (packed), a high number means
+    /// entities are very heterogeneous (sparse).
+    pub distinct_component_masks: usize,
+    /// Same as `component_counts`, indexed by the `TypeId` of the component.
+    counts_by_type: BTreeMap<TypeId, usize>,
+}
+
+impl EntityListStats {
+    /// Returns the number of entities having the component `C`.
+    ///
+    /// Returns 0 if `C` is not a component of the entity this list was created for.
+    pub fn count_of<C: 'static>(&self) -> usize {
+        self.counts_by_type.get(&TypeId::of::<C>()).copied().unwrap_or(0)
+    }
+}
+
+/// The memory used by an `EntityList`, in bytes.
+///
+/// Returned by `EntityList::memory_report`. Heap allocations made by the entities themselves
+/// (boxed components, `String`s, ...) are not counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryReport {
+    /// Bytes used by the live entities: `len() * size_of::<E>()`.
+    pub entity_bytes: usize,
+    /// Bytes reserved for entities by the arena, live or not: `capacity * size_of::<E>()`. The
+    /// arena stores a bit of bookkeeping next to every entity, which is not counted here.
+    pub arena_capacity_bytes: usize,
+    /// Bytes used by all the bitsets, see `EntityList::bitset_memory_usage`.
+    pub bitset_bytes: usize,
+}
+
+impl<E: EntityBase> EntityList<E> {
+    /// Computes statistics about the components of this list.
+    ///
+    /// Per-component counts come from the bitsets, but the number of distinct masks requires
+    /// visiting every entity, so this is not meant to be called every frame.
+    pub fn stats(&self) -> EntityListStats {
+        let mut component_counts = BTreeMap::new();
+        let mut counts_by_type = BTreeMap::new();
+        E::for_all_components(|component_index: usize| {
+            // with lazy bitsets, components no entity ever had have no bitset yet
+            let count = get_bitset(&self.bitsets, component_index).map_or(0, bitset_len);
+            if let Some(name) = E::component_name(component_index) {
+                component_counts.insert(name, count);
+            }
+            if let Some(type_id) = E::component_type_id(component_index) {
+                counts_by_type.insert(type_id, count);
+            }
+        });
+        let mut masks: HashSet<Vec<usize>> = HashSet::new();
+        for (_id, entity) in self.iter_all() {
+            let mut mask: Vec<usize> = Vec::new();
+            entity.for_each_active_component(|component_index: usize| {
+                mask.push(component_index);
+            });
+            masks.insert(mask);
+        }
+        EntityListStats {
+            entity_count: self.len(),
+            component_counts,
+            distinct_component_masks: masks.len(),
+            counts_by_type,
+        }
+    }
+
+    /// Returns how many entities have exactly `i` active components, for every `i`.
+    ///
+    /// The returned `Vec` has one slot per possible number of components, from 0 up to and
+    /// including the number of components declared for `E`.
+    pub fn component_count_histogram(&self) -> Vec<usize> {
+        let mut max_components: usize = 0;
+        E::for_all_components(|_| max_components += 1);
+        let mut histogram = vec![0; max_components + 1];
+        for (_id, entity) in self.iter_all() {
+            let mut count: usize = 0;
+            entity.for_each_active_component(|_| count += 1);
+            histogram[count] += 1;
+        }
+        histogram
+    }
+
+    /// Estimates the number of bytes used by all the bitsets of this list, change tracking
+    /// included.
+    ///
+    /// This is based on the length of every layer of the bitsets, the actual allocations may be
+    /// slightly bigger. Bitsets never shrink, so this only grows with the highest index ever used.
+    pub fn bitset_memory_usage(&self) -> usize {
+        self.bitsets.iter()
+            .flatten()
+            .chain(self.changed.values().map(ChangedSet::bitset))
+            .map(bitset_memory_usage)
+            .sum()
+    }
+
+    /// Reports the memory used by the entities and the bitsets of this list.
+    ///
+    /// Cheap enough to be called every frame, to keep track of memory in long-running games.
+    pub fn memory_report(&self) -> MemoryReport {
+        MemoryReport {
+            entity_bytes: self.len() * std::mem::size_of::<E>(),
+            arena_capacity_bytes: self.entities.capacity() * std::mem::size_of::<E>(),
+            bitset_bytes: self.bitset_memory_usage(),
+        }
+    }
+}
+
+/// Returns the number of bits set in the bitset.
+pub (crate) fn bitset_len(bitset: &BitSet) -> usize {
+    bitset.layer0_as_slice().iter().map(|word| word.count_ones() as usize).sum()
+}
+
+/// Returns the number of bits the bitset can hold without growing.
+pub (crate) fn bitset_capacity(bitset: &BitSet) -> usize {
+    std::mem::size_of_val(bitset.layer0_as_slice()) * 8
+}
+
+
+/// Returns the number of bytes used by the bitset, including its heap-allocated layers.
+pub (crate) fn bitset_memory_usage(bitset: &BitSet) -> usize {
+    std::mem::size_of::<BitSet>()
+        + std::mem::size_of_val(bitset.layer0_as_slice())
+        + std::mem::size_of_val(bitset.layer1_as_slice())
+        + std::mem::size_of_val(bitset.layer2_as_slice())
+}
\ No newline at end of file
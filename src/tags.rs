@@ -0,0 +1,38 @@
+use crate::{Component, EntityBase, EntityId, EntityList};
+
+/// Helpers for marker components, unit structs which only matter by their presence
+/// (`Dead`, `Selected`, ...).
+///
+/// Marker components are regular components, so all of this could be done with
+/// `add_component_for_entity` and `remove_component_for_entity`, these are only shorter.
+impl<E: EntityBase> EntityList<E> {
+    /// Adds the default-constructed component `C` to the given entity, if it exists.
+    ///
+    /// If the entity already had the component, it is replaced by the default one.
+    pub fn tag<C: Component<E> + Default>(&mut self, id: EntityId) {
+        self.add_component_for_entity(id, C::default());
+    }
+
+    /// Removes the component `C` from the given entity, if it exists.
+    pub fn untag<C: Component<E>>(&mut self, id: EntityId) {
+        self.remove_component_for_entity::<C>(id);
+    }
+
+    /// Returns true if the given entity exists and has the component `C`.
+    pub fn is_tagged<C: Component<E>>(&self, id: EntityId) -> bool {
+        self.get(id).is_some_and(|e| e.has::<C>())
+    }
+
+    /// Removes the component `C` from every entity which has it, and returns the ids of these
+    /// entities.
+    ///
+    /// This is the usual "collect the marked entities and reset the marker" pattern, for instance
+    /// to handle all the entities which were `Clicked` since the last call.
+    pub fn take_tagged<C: Component<E>>(&mut self) -> Vec<EntityId> {
+        let ids: Vec<EntityId> = self.iter::<(C,)>().map(|(id, _e)| id).collect();
+        for id in &ids {
+            self.remove_component_for_entity::<C>(*id);
+        }
+        ids
+    }
+}
\ No newline at end of file
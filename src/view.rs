@@ -0,0 +1,162 @@
+use crate::{Component, EntityBase, EntityId, EntityList};
+use crate::entity_list::bitset_index;
+use crate::iter::{MultiComponent, MultiComponentIter};
+use hibitset::BitSetLike;
+
+/// A read-only view of an `EntityList`, returned by `EntityList::as_readonly`.
+///
+/// The list cannot be mutated while a view is alive, so views are `Copy`, and `Send + Sync` as
+/// long as the entities are `Sync`: they can be handed to every task of a `rayon::scope` or to
+/// scoped threads.
+pub struct ReadOnlyView<'a, E: EntityBase> {
+    list: &'a EntityList<E>,
+}
+
+/// Read-only access to every entity of an `EntityList` but one, given by
+/// `EntityList::iter_mut_with_rest` along with the entity accessed mutably.
+///
+/// Looking up an entity costs a binary search over the live entities.
+pub struct RestView<'a, E: EntityBase> {
+    /// Entities before the excluded one, sorted by index.
+    before: &'a [(EntityId, &'a mut E)],
+    /// Entities after the excluded one, sorted by index.
+    after: &'a [(EntityId, &'a mut E)],
+}
+
+impl<E: EntityBase> EntityList<E> {
+    #[inline]
+    /// Returns a read-only view of this list, which can be shared across threads.
+    pub fn as_readonly(&self) -> ReadOnlyView<'_, E> {
+        ReadOnlyView {
+            list: self,
+        }
+    }
+
+    /// Same as `iter_mut`, but calls `f` for every matching entity, along with a `RestView` to read
+    /// every other entity of the list, for instance to move towards a target.
+    ///
+    /// This can't be an `Iterator`: the views of two steps would overlap with the entity of the
+    /// other step. The same warning as `iter_mut` applies, components must not be added or removed.
+    pub fn iter_mut_with_rest<C: for<'a> MultiComponent<'a, E>>(&mut self, mut f: impl FnMut(EntityId, &mut E, RestView<'_, E>)) {
+        let bitset = C::bitset(&self.bitsets);
+        // iter_mut goes through the arena by increasing index, so every part is sorted
+        let pooled = &self.pooled;
+        let mut entities: Vec<(EntityId, &mut E)> = self.entities.iter_mut()
+            .filter(|(id, _e)| !pooled.contains(bitset_index(id.into_raw_parts().0)))
+            .collect();
+        for i in 0..entities.len() {
+            if !bitset.contains(bitset_index(entities[i].0.into_raw_parts().0)) {
+                continue;
+            }
+            let (before, rest) = entities.split_at_mut(i);
+            let ((id, entity), after) = rest.split_first_mut().expect("i is in bounds");
+            f(*id, entity, RestView { before, after });
+        }
+    }
+}
+
+impl<'a, E: EntityBase> RestView<'a, E> {
+    /// Retrieves an entity, or `None` if it doesn't exist or is the excluded entity.
+    pub fn get(&self, id: EntityId) -> Option<&'a E> {
+        let index = id.into_raw_parts().0;
+        let part = match self.after.first() {
+            Some((first, _e)) if first.into_raw_parts().0 <= index => self.after,
+            _ => self.before,
+        };
+        let position = part.binary_search_by_key(&index, |(id, _e)| id.into_raw_parts().0).ok()?;
+        let (found_id, entity) = &part[position];
+        if *found_id == id {
+            Some(&**entity)
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    /// Retrieves a component of an entity, see `get`.
+    pub fn get_component<C: Component<E>>(&self, id: EntityId) -> Option<&'a C> {
+        self.get(id).and_then(C::get)
+    }
+
+    #[inline]
+    /// Returns true if the entity exists and is not the excluded entity.
+    pub fn contains(&self, id: EntityId) -> bool {
+        self.get(id).is_some()
+    }
+
+    #[inline]
+    /// Returns the number of entities in the view, the excluded entity not included.
+    pub fn len(&self) -> usize {
+        self.before.len() + self.after.len()
+    }
+
+    #[inline]
+    /// Returns true if the view has no entities, which happens when the list only holds the
+    /// excluded entity.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterates over every entity of the view, in the same order as `EntityList::iter_all`.
+    pub fn iter(&self) -> impl Iterator<Item=(EntityId, &'a E)> {
+        self.before.iter()
+            .chain(self.after.iter())
+            .map(|(id, entity)| (*id, &**entity))
+    }
+}
+
+impl<'a, E: EntityBase> ReadOnlyView<'a, E> {
+    #[inline]
+    /// See `EntityList::get`.
+    pub fn get(&self, id: EntityId) -> Option<&'a E> {
+        self.list.get(id)
+    }
+
+    #[inline]
+    /// See `EntityList::get_component`.
+    pub fn get_component<C: Component<E>>(&self, id: EntityId) -> Option<&'a C> {
+        self.list.get_component(id)
+    }
+
+    #[inline]
+    /// See `EntityList::contains`.
+    pub fn contains(&self, id: EntityId) -> bool {
+        self.list.contains(id)
+    }
+
+    #[inline]
+    /// See `EntityList::len`.
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    #[inline]
+    /// Returns true if the list has no entities, see `EntityList::len`.
+    pub fn is_empty(&self) -> bool {
+        self.list.len() == 0
+    }
+
+    /// See `EntityList::iter_all`.
+    pub fn iter_all(&self) -> impl Iterator<Item=(EntityId, &'a E)> {
+        self.list.iter_all()
+    }
+
+    /// See `EntityList::iter`.
+    pub fn iter<C: MultiComponent<'a, E>>(&self) -> MultiComponentIter<'a, E, C::BitSet> {
+        self.list.iter::<C>()
+    }
+
+    #[inline]
+    /// Returns the list this view was created from.
+    pub fn list(&self) -> &'a EntityList<E> {
+        self.list
+    }
+}
+
+impl<'a, E: EntityBase> Clone for ReadOnlyView<'a, E> {
+    fn clone(&self) -> ReadOnlyView<'a, E> {
+        *self
+    }
+}
+
+impl<'a, E: EntityBase> Copy for ReadOnlyView<'a, E> {}
\ No newline at end of file
@@ -0,0 +1,85 @@
+use std::marker::PhantomData;
+
+use crate::{EntityBase, EntityId, EntityList};
+
+/// A handle to an entity, which may or may not exist anymore.
+///
+/// An `EntityId` already knows the generation of the entity it points to, so this is mostly
+/// a typed wrapper, tied to a specific kind of entity, for code holding on to entities for
+/// a long time (UI, selection, ...). If the entity is removed and its slot is re-used by
+/// another entity, the handle correctly reports that its entity is gone.
+pub struct WeakEntity<E: EntityBase> {
+    id: EntityId,
+    _marker: PhantomData<fn() -> E>,
+}
+
+impl<E: EntityBase> WeakEntity<E> {
+    pub fn new(id: EntityId) -> WeakEntity<E> {
+        WeakEntity {
+            id,
+            _marker: PhantomData,
+        }
+    }
+
+    #[inline]
+    /// Returns the id of the entity this handle points to.
+    pub fn id(&self) -> EntityId {
+        self.id
+    }
+
+    #[inline]
+    /// Returns true if the entity still exists in the given list.
+    pub fn is_alive(&self, list: &EntityList<E>) -> bool {
+        list.contains(self.id)
+    }
+
+    #[inline]
+    /// Retrieves the entity immutably, if it still exists in the given list.
+    pub fn upgrade<'a>(&self, list: &'a EntityList<E>) -> Option<&'a E> {
+        list.get(self.id)
+    }
+
+    #[inline]
+    /// Retrieves the entity mutably, if it still exists in the given list.
+    ///
+    /// The same warning as `EntityList::get_mut` applies.
+    pub fn upgrade_mut<'a>(&self, list: &'a mut EntityList<E>) -> Option<&'a mut E> {
+        list.get_mut(self.id)
+    }
+}
+
+impl<E: EntityBase> From<EntityId> for WeakEntity<E> {
+    fn from(id: EntityId) -> WeakEntity<E> {
+        WeakEntity::new(id)
+    }
+}
+
+impl<E: EntityBase> Clone for WeakEntity<E> {
+    fn clone(&self) -> WeakEntity<E> {
+        *self
+    }
+}
+
+impl<E: EntityBase> Copy for WeakEntity<E> {}
+
+impl<E: EntityBase> PartialEq for WeakEntity<E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<E: EntityBase> Eq for WeakEntity<E> {}
+
+impl<E: EntityBase> std::fmt::Debug for WeakEntity<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_tuple("WeakEntity").field(&self.id).finish()
+    }
+}
+
+impl<E: EntityBase> EntityList<E> {
+    #[inline]
+    /// Returns a weak handle for the given id.
+    pub fn weak(&self, id: EntityId) -> WeakEntity<E> {
+        WeakEntity::new(id)
+    }
+}
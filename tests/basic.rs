@@ -139,6 +139,74 @@ fn entity_with_component_change() {
     debug_assert_eq!(e.get::<ComponentA>(), Some(&ComponentA { alpha: 15.0 }));
 }
 
+#[test]
+fn entity_with_component_change_mutate_or_insert() {
+    use mobec::ChangeComponent;
+
+    let mutate_or_insert = |_: &mut Entity| -> ChangeComponent<ComponentA> {
+        ChangeComponent::MutateOrInsert(
+            Box::new(|a: &mut ComponentA| a.alpha += 1.0),
+            Box::new(|| ComponentA { alpha: 0.0 }),
+        )
+    };
+
+    // absent: inserted then mutated
+    let e = Entity::new((CommonProp, AgeProp { age: 5 }));
+    let e = e.with_component_change(mutate_or_insert);
+    debug_assert_eq!(e.get::<ComponentA>(), Some(&ComponentA { alpha: 1.0 }));
+
+    // present: only mutated
+    let e = e.with(ComponentA { alpha: 5.0 });
+    let e = e.with_component_change(mutate_or_insert);
+    debug_assert_eq!(e.get::<ComponentA>(), Some(&ComponentA { alpha: 6.0 }));
+}
+
+#[test]
+fn entity_with_component_change_returning() {
+    use mobec::ChangeComponent;
+
+    let e = Entity::new((CommonProp, AgeProp { age: 5 }))
+            .with(ComponentA { alpha: 5.0 });
+
+    let (e, old) = e.with_component_change_returning(|e: &mut Entity| -> (ChangeComponent<ComponentA>, Option<ComponentA>) {
+        (ChangeComponent::Replace(ComponentA { alpha: 6.0 }), e.get::<ComponentA>().copied())
+    });
+    debug_assert_eq!(old, Some(ComponentA { alpha: 5.0 }));
+    debug_assert_eq!(e.get::<ComponentA>(), Some(&ComponentA { alpha: 6.0 }));
+
+    let (e, removed) = e.with_component_change_returning(|e: &mut Entity| -> (ChangeComponent<ComponentA>, Option<ComponentA>) {
+        (ChangeComponent::Remove, e.get::<ComponentA>().copied())
+    });
+    debug_assert_eq!(removed, Some(ComponentA { alpha: 6.0 }));
+    debug_assert_eq!(e.get::<ComponentA>(), None);
+}
+
+#[test]
+fn entity_try_with_component_change() {
+    use mobec::ChangeComponent;
+
+    let e = Entity::new((CommonProp, AgeProp { age: 5 }))
+            .with(ComponentB { beta: 5 });
+    let e = e.try_with_component_change(|e: &mut Entity| -> Result<ChangeComponent<ComponentA>, String> {
+        match e.get::<ComponentB>() {
+            Some(ComponentB { beta }) if *beta >= 0 => Ok(ChangeComponent::Replace(ComponentA { alpha: *beta as f32 })),
+            _ => Err(String::from("beta must be positive")),
+        }
+    }).expect("beta is positive");
+
+    debug_assert_eq!(e.get::<ComponentA>(), Some(&ComponentA { alpha: 5.0 }));
+
+    let e = e.with(ComponentB { beta: -1 });
+    let r = e.try_with_component_change(|e: &mut Entity| -> Result<ChangeComponent<ComponentA>, String> {
+        match e.get::<ComponentB>() {
+            Some(ComponentB { beta }) if *beta >= 0 => Ok(ChangeComponent::Replace(ComponentA { alpha: *beta as f32 })),
+            _ => Err(String::from("beta must be positive")),
+        }
+    });
+
+    debug_assert_eq!(r.err(), Some(String::from("beta must be positive")));
+}
+
 #[test]
 /// Tests that properties are available
 fn entity_prop_ops() {
@@ -327,4 +395,415 @@ fn iter_refresh() {
     debug_assert_eq!(only_comp_a, &[id_1, id_2, id_3, id_6]);
     debug_assert_eq!(only_comp_b, &[id_2, id_3, id_5]);
     debug_assert_eq!(only_comp_c, &[id_4, id_5, id_6]);
+}
+#[test]
+/// Tests that both mutable iterations yield every matching entity exactly once.
+fn iter_mut_scan() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    for i in 0..100 {
+        let e = Entity::new((CommonProp, AgeProp { age: i }));
+        let e = if i % 2 == 0 { e.with(ComponentA { alpha: i as f32 }) } else { e };
+        let e = if i % 3 == 0 { e.with(ComponentB { beta: i as i32 }) } else { e };
+        entity_list.insert(e);
+    }
+
+    let with_bitsets: Vec<_> = entity_list.iter_mut::<(ComponentA, ComponentB)>().map(|(i, _e)| i).collect();
+    let with_scan: Vec<_> = entity_list.iter_mut_scan::<(ComponentA, ComponentB)>().map(|(i, _e)| i).collect();
+    debug_assert_eq!(with_bitsets.len(), 17);
+    debug_assert_eq!(with_bitsets, with_scan);
+
+    for (_i, e) in entity_list.iter_mut_scan::<(ComponentA,)>() {
+        e.age.age += 1000;
+    }
+    debug_assert_eq!(entity_list.iter_all().filter(|(_i, e)| e.age.age >= 1000).count(), 50);
+}
+
+#[test]
+fn for_each_component_mut() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    for i in 0..20 {
+        let e = Entity::new((CommonProp, AgeProp { age: i }));
+        let e = if i % 2 == 0 { e.with(ComponentA { alpha: i as f32 }) } else { e };
+        let e = if i % 3 == 0 { e.with(ComponentB { beta: i as i32 }) } else { e };
+        entity_list.insert(e);
+    }
+    let mut manual = entity_list.clone();
+
+    let mut visited = Vec::new();
+    entity_list.for_each_component_mut(|id, a: &mut ComponentA| {
+        a.alpha *= 2.0;
+        visited.push(id);
+    });
+    for (_id, e) in manual.iter_mut::<(ComponentA,)>() {
+        e.get_mut::<ComponentA>().unwrap().alpha *= 2.0;
+    }
+
+    debug_assert_eq!(visited.len(), 10);
+    let alphas: Vec<_> = entity_list.iter::<(ComponentA,)>().map(|(i, e)| (i, e.get::<ComponentA>().unwrap().alpha)).collect();
+    let manual_alphas: Vec<_> = manual.iter::<(ComponentA,)>().map(|(i, e)| (i, e.get::<ComponentA>().unwrap().alpha)).collect();
+    debug_assert_eq!(alphas, manual_alphas);
+    debug_assert_eq!(alphas.iter().map(|(i, _a)| *i).collect::<Vec<_>>(), visited);
+}
+
+#[test]
+fn for_each_pair() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    for i in 0..20 {
+        let e = Entity::new((CommonProp, AgeProp { age: i }));
+        let e = if i % 3 == 0 { e.with(ComponentB { beta: i as i32 }) } else { e };
+        entity_list.insert(e);
+    }
+    let n = entity_list.iter::<(ComponentB,)>().count();
+    debug_assert_eq!(n, 7);
+
+    let mut pairs = Vec::new();
+    entity_list.for_each_pair(|(id_1, b_1): (_, &ComponentB), (id_2, b_2): (_, &ComponentB)| {
+        debug_assert!(b_1.beta < b_2.beta);
+        pairs.push((id_1, id_2));
+    });
+    debug_assert_eq!(pairs.len(), n * (n - 1) / 2);
+    pairs.sort();
+    pairs.dedup();
+    debug_assert_eq!(pairs.len(), n * (n - 1) / 2);
+
+    let mut count = 0;
+    entity_list.for_each_pair(|_: (_, &ComponentC), _: (_, &ComponentC)| count += 1);
+    debug_assert_eq!(count, 0);
+}
+
+#[test]
+fn for_each_pair_mut() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    for i in 0..10 {
+        let e = Entity::new((CommonProp, AgeProp { age: 0 }));
+        let e = if i % 2 == 0 { e.with(ComponentA { alpha: i as f32 }) } else { e };
+        entity_list.insert(e);
+    }
+
+    // every entity with A meets every other entity with A once
+    entity_list.for_each_pair_mut::<ComponentA>(|id_1, e_1, id_2, e_2| {
+        debug_assert!(id_1.into_raw_parts().0 < id_2.into_raw_parts().0);
+        e_1.age.age += 1;
+        e_2.age.age += 1;
+    });
+
+    let ages: Vec<_> = entity_list.iter_all().map(|(_i, e)| e.age.age).collect();
+    debug_assert_eq!(ages, &[4, 0, 4, 0, 4, 0, 4, 0, 4, 0]);
+    debug_assert_eq!(ages.iter().sum::<u32>(), 2 * (5 * 4 / 2));
+}
+
+#[test]
+fn iter_range() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    for i in 0..100 {
+        let e = Entity::new((CommonProp, AgeProp { age: i }));
+        let e = if i % 2 == 0 { e.with(ComponentA { alpha: i as f32 }) } else { e };
+        let e = if i % 3 == 0 { e.with(ComponentB { beta: i as i32 }) } else { e };
+        entity_list.insert(e);
+    }
+
+    let full: Vec<_> = entity_list.iter::<(ComponentA, ComponentB)>().map(|(i, _e)| i).collect();
+    for mid in [0, 1, 6, 7, 50, 99, 100].iter() {
+        let mut ranged: Vec<_> = entity_list.iter_range::<(ComponentA, ComponentB)>(0, *mid).map(|(i, _e)| i).collect();
+        debug_assert!(ranged.iter().all(|i| (i.into_raw_parts().0 as u32) < *mid));
+        ranged.extend(entity_list.iter_range::<(ComponentA, ComponentB)>(*mid, 100).map(|(i, _e)| i));
+        debug_assert_eq!(ranged, full);
+    }
+
+    let ages: Vec<_> = entity_list.iter_range::<(ComponentB,)>(10, 20).map(|(_i, e)| e.age.age).collect();
+    debug_assert_eq!(ages, &[12, 15, 18]);
+}
+
+#[test]
+fn iter_filter() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    for i in 0..10 {
+        let e = Entity::new((CommonProp, AgeProp { age: i }));
+        let e = if i < 8 { e.with(ComponentB { beta: i as i32 - 4 }) } else { e };
+        entity_list.insert(e);
+    }
+
+    let ages: Vec<_> = entity_list.iter_filter(|b: &ComponentB| b.beta.abs() <= 1).map(|(_i, e)| e.age.age).collect();
+    debug_assert_eq!(ages, &[3, 4, 5]);
+    debug_assert_eq!(entity_list.iter_filter(|_b: &ComponentB| true).count(), 8);
+    debug_assert_eq!(entity_list.iter_filter(|_a: &ComponentA| true).count(), 0);
+}
+
+#[test]
+fn fold_query() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    for i in 0..10 {
+        let e = Entity::new((CommonProp, AgeProp { age: i }));
+        let e = if i < 6 { e.with(ComponentB { beta: i as i32 }) } else { e };
+        let e = if i >= 3 { e.with(ComponentC { ceta: 1 }) } else { e };
+        entity_list.insert(e);
+    }
+
+    let total_beta = entity_list.fold_query::<(ComponentB,), _>(0, |acc, _id, e| acc + e.get::<ComponentB>().unwrap().beta);
+    debug_assert_eq!(total_beta, 15);
+    let total_beta = entity_list.fold_query::<(ComponentB, ComponentC), _>(0, |acc, _id, e| acc + e.get::<ComponentB>().unwrap().beta);
+    debug_assert_eq!(total_beta, 12);
+    let (count, total_age) = entity_list.fold_query::<(ComponentC,), _>((0, 0), |(count, total), _id, e| (count + 1, total + e.age.age));
+    debug_assert_eq!((count, total_age), (7, 42));
+    debug_assert_eq!(entity_list.fold_query::<(ComponentA,), _>(0, |acc, _id, _e| acc + 1), 0);
+}
+
+#[test]
+fn nth_matching() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    for i in 0..10 {
+        let e = Entity::new((CommonProp, AgeProp { age: i }));
+        let e = if i % 3 == 0 { e.with(ComponentB { beta: i as i32 }) } else { e };
+        entity_list.insert(e);
+    }
+
+    let age_of = |n| entity_list.nth_matching::<(ComponentB,)>(n).map(|(_i, e)| e.age.age);
+    debug_assert_eq!(age_of(0), Some(0));
+    debug_assert_eq!(age_of(2), Some(6));
+    debug_assert_eq!(age_of(3), Some(9));
+    debug_assert_eq!(age_of(4), None);
+    debug_assert_eq!(age_of(100), None);
+    debug_assert!(entity_list.nth_matching::<(ComponentA,)>(0).is_none());
+}
+
+#[test]
+fn page() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    for i in 0..20 {
+        let e = Entity::new((CommonProp, AgeProp { age: i }));
+        let e = if i % 2 == 0 { e.with(ComponentB { beta: i as i32 }) } else { e };
+        entity_list.insert(e);
+    }
+
+    let ages = |offset, limit| -> Vec<u32> {
+        entity_list.page::<(ComponentB,)>(offset, limit).into_iter().map(|(_i, e)| e.age.age).collect()
+    };
+    debug_assert_eq!(ages(0, 3), &[0, 2, 4]);
+    debug_assert_eq!(ages(4, 3), &[8, 10, 12]);
+    debug_assert_eq!(ages(8, 5), &[16, 18]);
+    debug_assert!(ages(10, 5).is_empty());
+    debug_assert!(ages(50, 5).is_empty());
+    debug_assert!(ages(2, 0).is_empty());
+    debug_assert_eq!(ages(0, 100).len(), 10);
+}
+
+#[test]
+fn for_each_property_mut() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    let ids: Vec<_> = (0..10).map(|i| {
+        let e = Entity::new((CommonProp, AgeProp { age: i }));
+        entity_list.insert(if i % 3 == 0 { e.with(ComponentA { alpha: 0.0 }) } else { e })
+    }).collect();
+    entity_list.remove(ids[4]);
+
+    let mut visited = Vec::new();
+    entity_list.for_each_property_mut(|id, e| {
+        visited.push(id);
+        e.age.age *= 2;
+    });
+    debug_assert_eq!(visited.len(), 9);
+    debug_assert!(!visited.contains(&ids[4]));
+    let ages: Vec<_> = entity_list.iter_all().map(|(_i, e)| e.age.age).collect();
+    debug_assert_eq!(ages, &[0, 2, 4, 6, 10, 12, 14, 16, 18]);
+}
+
+#[test]
+/// Tests that the empty query matches every live entity, and skips the free slots.
+fn empty_query() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    let ids: Vec<_> = (0..10).map(|i| {
+        let e = Entity::new((CommonProp, AgeProp { age: i }));
+        entity_list.insert(if i % 3 == 0 { e.with(ComponentA { alpha: 0.0 }) } else { e })
+    }).collect();
+    entity_list.remove(ids[0]);
+    entity_list.remove(ids[4]);
+    entity_list.remove(ids[9]);
+    let live: Vec<_> = entity_list.iter_all().map(|(id, _e)| id).collect();
+
+    debug_assert_eq!(entity_list.iter::<()>().map(|(id, _e)| id).collect::<Vec<_>>(), live);
+    debug_assert_eq!(entity_list.iter::<()>().remaining(), 7);
+    debug_assert_eq!(entity_list.fold_query::<(), _>(0, |count, _id, _e| count + 1), 7);
+    debug_assert_eq!(entity_list.page::<()>(2, 3).iter().map(|(id, _e)| *id).collect::<Vec<_>>(), &live[2..5]);
+    debug_assert_eq!(entity_list.nth_matching::<()>(6).map(|(id, _e)| id), Some(live[6]));
+    debug_assert!(entity_list.nth_matching::<()>(7).is_none());
+    debug_assert_eq!(entity_list.iter_range::<()>(0, u32::MAX).count(), 7);
+
+    for (_id, e) in entity_list.iter_mut::<()>() {
+        e.age.age += 100;
+    }
+    debug_assert!(entity_list.iter_all().all(|(_id, e)| e.age.age >= 100));
+
+    let empty: EntityList<Entity> = EntityList::new();
+    debug_assert_eq!(empty.iter::<()>().count(), 0);
+}
+
+#[test]
+fn iter_prioritized() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    for (age, alpha) in [(3, 1.0), (1, 2.0), (2, 3.0), (1, 4.0), (3, 5.0)].iter() {
+        entity_list.insert(Entity::new((CommonProp, AgeProp { age: *age })).with(ComponentA { alpha: *alpha }));
+    }
+    entity_list.insert(Entity::new((CommonProp, AgeProp { age: 0 })));
+
+    let alphas: Vec<_> = entity_list.iter_prioritized::<(ComponentA,), _>(|e| e.age.age as i32)
+        .map(|(_i, e)| e.get::<ComponentA>().unwrap().alpha)
+        .collect();
+    debug_assert_eq!(alphas, &[2.0, 4.0, 3.0, 1.0, 5.0]);
+}
+
+#[test]
+fn index_shards() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    let ids: Vec<_> = (0..100).map(|i| {
+        entity_list.insert(Entity::new((CommonProp, AgeProp { age: i })))
+    }).collect();
+    // leave a big hole at the beginning, so that the raw index space is unbalanced
+    for id in &ids[0..60] {
+        entity_list.remove(*id);
+    }
+    entity_list.remove(ids[99]);
+
+    let count_in = |(start, end): (u32, u32)| {
+        entity_list.iter_all()
+            .filter(|(id, _e)| {
+                let index = id.into_raw_parts().0 as u32;
+                index >= start && index < end
+            })
+            .count()
+    };
+
+    for n in 1..=8 {
+        let shards = entity_list.index_shards(n);
+        debug_assert_eq!(shards.len(), n);
+        debug_assert_eq!(shards[0].0, 0);
+        debug_assert_eq!(shards[n - 1].1, 99);
+        for w in shards.windows(2) {
+            debug_assert!(w[0].0 <= w[0].1);
+            debug_assert_eq!(w[0].1, w[1].0);
+        }
+        let counts: Vec<usize> = shards.iter().map(|shard| count_in(*shard)).collect();
+        debug_assert_eq!(counts.iter().sum::<usize>(), 39);
+        debug_assert!(counts.iter().max().unwrap() - counts.iter().min().unwrap() <= 1);
+    }
+
+    let empty: EntityList<Entity> = EntityList::new();
+    debug_assert_eq!(empty.index_shards(3), vec![(0, 0); 3]);
+}
+
+#[test]
+fn iter_remaining() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    for i in 0..30 {
+        let e = Entity::new((CommonProp, AgeProp { age: i }));
+        let e = if i % 2 == 0 { e.with(ComponentA { alpha: i as f32 }) } else { e };
+        let e = if i % 3 == 0 { e.with(ComponentB { beta: i as i32 }) } else { e };
+        entity_list.insert(e);
+    }
+
+    let mut iter = entity_list.iter::<(ComponentA, ComponentB)>();
+    debug_assert_eq!(iter.remaining(), 5);
+    for expected_remaining in (0..5).rev() {
+        debug_assert!(iter.next().is_some());
+        debug_assert_eq!(iter.remaining(), expected_remaining);
+    }
+    debug_assert!(iter.next().is_none());
+    debug_assert_eq!(iter.remaining(), 0);
+
+    let mut iter = entity_list.iter_mut::<(ComponentB,)>();
+    debug_assert_eq!(iter.remaining(), 10);
+    iter.next();
+    iter.next();
+    debug_assert_eq!(iter.remaining(), 8);
+}
+
+#[test]
+#[should_panic(expected = "yielded index 0 after index 1")]
+/// Tests that a bitset iterator yielding the same index twice panics instead of aliasing entities.
+fn iter_mut_guard() {
+    use generational_arena::Arena;
+    use hibitset::{BitIter, BitSet};
+    use mobec::iter::MultiComponentIterMut;
+
+    let mut arena: Arena<Entity> = Arena::new();
+    arena.insert(Entity::new((CommonProp, AgeProp { age: 1 })));
+    arena.insert(Entity::new((CommonProp, AgeProp { age: 2 })));
+
+    let mut bitset = BitSet::new();
+    bitset.add(0);
+    bitset.add(1);
+
+    // A correct iterator would start with the layer 0 mask empty. Starting with it full makes the
+    // iterator yield indices 0 and 1 twice.
+    let iter = BitIter::new(&bitset, [0b11, 0b1, 0, 0], [0, 0, 0]);
+    let v: Vec<_> = MultiComponentIterMut::new(iter, &mut arena).collect();
+    drop(v);
+}
+
+#[test]
+/// Tests that an out of date bitset panics with a message naming the components and the index.
+fn out_of_date_bitset_message() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    use generational_arena::Arena;
+    use hibitset::BitSet;
+    use mobec::Component;
+    use mobec::iter::MultiComponent;
+
+    let mut arena: Arena<Entity> = Arena::new();
+    arena.insert(Entity::new((CommonProp, AgeProp { age: 1 })).with(ComponentA { alpha: 1.0 }).with(ComponentB { beta: 1 }));
+
+    // index 3 is in both bitsets, but there is no entity there.
+    let mut bitsets: Vec<Option<BitSet>> = vec![None; 3];
+    for component_index in [<ComponentA as Component<Entity>>::INDEX, <ComponentB as Component<Entity>>::INDEX].iter() {
+        let mut bitset = BitSet::new();
+        bitset.add(0);
+        bitset.add(3);
+        bitsets[*component_index] = Some(bitset);
+    }
+
+    let panic = catch_unwind(AssertUnwindSafe(|| {
+        <(ComponentA, ComponentB) as MultiComponent<Entity>>::iter(&bitsets, &arena).count()
+    })).unwrap_err();
+    let message = panic.downcast_ref::<String>().unwrap();
+    debug_assert!(message.contains("bitset of (a, b)"), "{}", message);
+    debug_assert!(message.contains("index 3"), "{}", message);
+
+    let panic = catch_unwind(AssertUnwindSafe(|| {
+        <(ComponentB,) as MultiComponent<Entity>>::iter_mut(&bitsets, &mut arena).count()
+    })).unwrap_err();
+    let message = panic.downcast_ref::<String>().unwrap();
+    debug_assert!(message.contains("bitset of (b)"), "{}", message);
+    debug_assert!(message.contains("index 3"), "{}", message);
+}
+
+#[test]
+fn iter_for_components() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+    let id_1 = entity_list.insert(Entity::new((CommonProp, AgeProp { age: 1 })).with(ComponentA { alpha: 1.0 }));
+    let id_2 = entity_list.insert(Entity::new((CommonProp, AgeProp { age: 2 })).with(ComponentA { alpha: 2.0 }).with(ComponentB { beta: 2 }));
+    entity_list.insert(Entity::new((CommonProp, AgeProp { age: 3 })));
+
+    let ids: Vec<_> = entity_list.iter_for_components::<(ComponentA,)>().map(|(id, _e)| id).collect();
+    debug_assert_eq!(ids, vec![id_1, id_2]);
+    let old_ids: Vec<_> = entity_list.iter::<(ComponentA,)>().map(|(id, _e)| id).collect();
+    debug_assert_eq!(ids, old_ids);
+
+    for (_id, entity) in entity_list.iter_for_components_mut::<(ComponentA, ComponentB)>() {
+        entity.age.age += 10;
+    }
+    debug_assert_eq!(entity_list.get(id_1).unwrap().age.age, 1);
+    debug_assert_eq!(entity_list.get(id_2).unwrap().age.age, 12);
 }
\ No newline at end of file
@@ -0,0 +1,249 @@
+use mobec::{
+    define_entity,
+    EntityList,
+    EntityBase,
+    Tick,
+};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ComponentA {
+    alpha: f32,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ComponentB {
+    beta: i32,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct CommonProp;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Damaged {
+    amount: f32,
+}
+
+define_entity! {
+    #[derive(Debug)]
+    pub struct Entity {
+        props => {
+            common: CommonProp,
+        },
+        components => {
+            a => ComponentA,
+            b => ComponentB,
+        },
+        events => {
+            damaged => Damaged,
+        }
+    }
+}
+
+#[test]
+/// Tests that only the entities whose tracked component changed are iterated over.
+fn iter_changed() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+    entity_list.track_changes::<ComponentA>();
+
+    let id_1 = entity_list.insert(
+        Entity::new((CommonProp,))
+            .with(ComponentA { alpha: 1.0 })
+    );
+    let id_2 = entity_list.insert(
+        Entity::new((CommonProp,))
+            .with(ComponentA { alpha: 2.0 })
+    );
+    let id_3 = entity_list.insert(
+        Entity::new((CommonProp,))
+            .with(ComponentB { beta: 3 })
+    );
+
+    // insertions count as changes
+    let changed: Vec<_> = entity_list.iter_changed::<ComponentA>(Tick(0)).map(|(i, _e)| i).collect();
+    debug_assert_eq!(changed, &[id_1, id_2]);
+
+    entity_list.advance_tick();
+    let changed: Vec<_> = entity_list.iter_changed::<ComponentA>(Tick(1)).map(|(i, _e)| i).collect();
+    debug_assert!(changed.is_empty());
+
+    entity_list.add_component_for_entity(id_3, ComponentA { alpha: 3.0 });
+    if let Some(e) = entity_list.get_mut(id_1) {
+        e.mutate(|a: &mut ComponentA| a.alpha += 1.0);
+    }
+    entity_list.mark_changed::<ComponentA>(id_1);
+
+    let changed: Vec<_> = entity_list.iter_changed::<ComponentA>(Tick(1)).map(|(i, _e)| i).collect();
+    debug_assert_eq!(changed, &[id_1, id_3]);
+
+    // a tick in the future has seen no change yet
+    debug_assert_eq!(entity_list.iter_changed::<ComponentA>(Tick(2)).count(), 0);
+
+    // removed entities are not reported anymore
+    entity_list.remove(id_3);
+    let changed: Vec<_> = entity_list.iter_changed::<ComponentA>(Tick(1)).map(|(i, _e)| i).collect();
+    debug_assert_eq!(changed, &[id_1]);
+}
+
+#[test]
+/// Tests that untracked components are never reported as changed.
+fn iter_changed_untracked() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+    entity_list.track_changes::<ComponentA>();
+
+    let id_1 = entity_list.insert(
+        Entity::new((CommonProp,))
+            .with(ComponentB { beta: 1 })
+    );
+    entity_list.add_component_for_entity(id_1, ComponentB { beta: 2 });
+
+    debug_assert_eq!(entity_list.iter_changed::<ComponentB>(Tick(0)).count(), 0);
+}
+
+#[test]
+/// Tests that changes persist within a tick, and that a new tick starts without changes.
+fn tick_boundaries() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+    entity_list.track_changes::<ComponentA>();
+    entity_list.track_changes::<ComponentB>();
+
+    debug_assert_eq!(entity_list.current_tick(), Tick(0));
+
+    let id_1 = entity_list.insert(
+        Entity::new((CommonProp,))
+            .with(ComponentA { alpha: 1.0 })
+            .with(ComponentB { beta: 1 })
+    );
+
+    let tick = entity_list.current_tick();
+    // several systems may read the changes during the same tick
+    for _ in 0..3 {
+        let changed: Vec<_> = entity_list.iter_changed::<ComponentA>(tick).map(|(i, _e)| i).collect();
+        debug_assert_eq!(changed, &[id_1]);
+    }
+
+    // clearing a single component leaves the others untouched
+    entity_list.clear_changed::<ComponentA>();
+    debug_assert_eq!(entity_list.iter_changed::<ComponentA>(tick).count(), 0);
+    debug_assert_eq!(entity_list.iter_changed::<ComponentB>(tick).count(), 1);
+
+    entity_list.advance_tick();
+    debug_assert_eq!(entity_list.current_tick(), Tick(1));
+    debug_assert_eq!(entity_list.iter_changed::<ComponentB>(entity_list.current_tick()).count(), 0);
+    // the changes of the previous tick are forgotten
+    debug_assert_eq!(entity_list.iter_changed::<ComponentB>(tick).count(), 0);
+
+    entity_list.add_component_for_entity(id_1, ComponentB { beta: 2 });
+    debug_assert_eq!(entity_list.iter_changed::<ComponentB>(entity_list.current_tick()).count(), 1);
+}
+
+#[test]
+/// Tests that `iter_changed` only yields the changes of the current tick, whatever the given tick.
+fn iter_changed_since() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+    entity_list.track_changes::<ComponentA>();
+
+    let ids: Vec<_> = (0..4)
+        .map(|i| entity_list.insert(Entity::new((CommonProp,)).with(ComponentA { alpha: i as f32 })))
+        .collect();
+    // tick 1: ids[1] changes, tick 2: ids[2] changes, tick 3: ids[1] and ids[3] change
+    for (tick, changed) in [(1, &[ids[1]][..]), (2, &[ids[2]][..]), (3, &[ids[3], ids[1]][..])].iter() {
+        entity_list.advance_tick();
+        debug_assert_eq!(entity_list.current_tick(), Tick(*tick));
+        for id in changed.iter() {
+            entity_list.mutate_component(*id, |a: &mut ComponentA| a.alpha += 1.0);
+        }
+    }
+
+    let changed_since = |entity_list: &EntityList<Entity>, tick: u64| -> Vec<_> {
+        entity_list.iter_changed::<ComponentA>(Tick(tick)).map(|(i, _e)| i).collect()
+    };
+    debug_assert_eq!(changed_since(&entity_list, 0), &[ids[1], ids[3]]);
+    debug_assert_eq!(changed_since(&entity_list, 3), &[ids[1], ids[3]]);
+    debug_assert!(changed_since(&entity_list, 4).is_empty());
+
+    entity_list.clear_changed::<ComponentA>();
+    debug_assert!(changed_since(&entity_list, 0).is_empty());
+}
+
+#[test]
+/// Tests that mutable access through the list marks the component as changed.
+fn get_component_mut_marks_changed() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    let id_1 = entity_list.insert(
+        Entity::new((CommonProp,))
+            .with(ComponentA { alpha: 1.0 })
+    );
+    let id_2 = entity_list.insert(
+        Entity::new((CommonProp,))
+            .with(ComponentA { alpha: 2.0 })
+    );
+    entity_list.track_changes::<ComponentA>();
+
+    if let Some(a) = entity_list.get_component_mut::<ComponentA>(id_2) {
+        a.alpha += 1.0;
+    }
+    let changed: Vec<_> = entity_list.iter_changed::<ComponentA>(entity_list.current_tick()).map(|(i, _e)| i).collect();
+    debug_assert_eq!(changed, &[id_2]);
+    debug_assert!(entity_list.get_component::<ComponentA>(id_1).is_some());
+}
+
+#[test]
+/// Tests that event components only last until the next tick.
+fn events_cleared_on_advance_tick() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    let id_1 = entity_list.insert(
+        Entity::new((CommonProp,))
+            .with(ComponentA { alpha: 1.0 })
+    );
+    let id_2 = entity_list.insert(
+        Entity::new((CommonProp,))
+            .with(Damaged { amount: 3.0 })
+    );
+    entity_list.add_component_for_entity(id_1, Damaged { amount: 1.0 });
+
+    let damaged: Vec<_> = entity_list.iter::<(Damaged,)>().map(|(i, e)| (i, e.get::<Damaged>().unwrap().amount)).collect();
+    debug_assert_eq!(damaged, &[(id_1, 1.0), (id_2, 3.0)]);
+
+    entity_list.advance_tick();
+
+    debug_assert_eq!(entity_list.iter::<(Damaged,)>().count(), 0);
+    debug_assert!(!entity_list.get(id_1).unwrap().has::<Damaged>());
+    debug_assert!(!entity_list.get(id_2).unwrap().has::<Damaged>());
+    // regular components are kept
+    debug_assert!(entity_list.get(id_1).unwrap().has::<ComponentA>());
+
+    // events added during the next tick are visible again
+    entity_list.add_component_for_entity(id_2, Damaged { amount: 2.0 });
+    let damaged: Vec<_> = entity_list.iter::<(Damaged,)>().map(|(i, _e)| i).collect();
+    debug_assert_eq!(damaged, &[id_2]);
+}
+
+#[test]
+/// Tests that clearing the events forgets the changes of the tracked event components.
+fn clear_events_drops_changes() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+    entity_list.track_changes::<Damaged>();
+    entity_list.track_changes::<ComponentA>();
+
+    let id_1 = entity_list.insert(
+        Entity::new((CommonProp,))
+            .with(ComponentA { alpha: 1.0 })
+            .with(Damaged { amount: 1.0 })
+    );
+    let changed: Vec<_> = entity_list.iter_changed::<Damaged>(entity_list.current_tick()).map(|(i, _e)| i).collect();
+    debug_assert_eq!(changed, &[id_1]);
+
+    entity_list.clear_events();
+    debug_assert_eq!(entity_list.current_tick(), Tick(0));
+    debug_assert!(!entity_list.get(id_1).unwrap().has::<Damaged>());
+    // the change of the regular component is kept
+    debug_assert_eq!(entity_list.iter_changed::<ComponentA>(entity_list.current_tick()).count(), 1);
+
+    // the event comes back without being marked, so its old change must not show up again
+    entity_list.get_mut(id_1).unwrap().damaged = Some(Box::new(Damaged { amount: 2.0 }));
+    entity_list.refresh(id_1);
+    debug_assert_eq!(entity_list.iter::<(Damaged,)>().count(), 1);
+    debug_assert_eq!(entity_list.iter_changed::<Damaged>(entity_list.current_tick()).count(), 0);
+}
\ No newline at end of file
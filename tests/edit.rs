@@ -0,0 +1,137 @@
+use mobec::{
+    define_entity,
+    EntityList,
+    EntityBase,
+};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ComponentA {
+    alpha: f32,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ComponentB {
+    beta: i32,
+}
+
+define_entity! {
+    #[derive(Debug)]
+    pub struct Entity {
+        props => {},
+        components => {
+            a => ComponentA,
+            b => ComponentB,
+        }
+    }
+}
+
+#[test]
+/// Tests that components added and removed during a session are visible to queries afterwards.
+fn edit_session() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    let ids: Vec<_> = (0..6)
+        .map(|i| entity_list.insert(Entity::new(()).with(ComponentA { alpha: i as f32 })))
+        .collect();
+
+    {
+        let mut session = entity_list.edit_session();
+        for id in &ids[0..3] {
+            let e = session.get_mut(*id).unwrap();
+            e.b = Some(Box::new(ComponentB { beta: 1 }));
+        }
+        session.get_mut(ids[1]).unwrap().a = None;
+        // reading through the session is allowed
+        debug_assert_eq!(session.len(), 6);
+    }
+
+    let with_a: Vec<_> = entity_list.iter::<(ComponentA,)>().map(|(i, _e)| i).collect();
+    let with_b: Vec<_> = entity_list.iter::<(ComponentB,)>().map(|(i, _e)| i).collect();
+    let with_ab: Vec<_> = entity_list.iter::<(ComponentA, ComponentB)>().map(|(i, _e)| i).collect();
+    debug_assert_eq!(with_a, &[ids[0], ids[2], ids[3], ids[4], ids[5]]);
+    debug_assert_eq!(with_b, &ids[0..3]);
+    debug_assert_eq!(with_ab, &[ids[0], ids[2]]);
+}
+
+#[test]
+/// Tests that components added and removed during a safe iteration are visible to queries after the loop.
+fn iter_mut_safe() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    let ids: Vec<_> = (0..4)
+        .map(|i| entity_list.insert(Entity::new(()).with(ComponentA { alpha: i as f32 })))
+        .collect();
+
+    let mut visited = Vec::new();
+    entity_list.iter_mut_safe::<(ComponentA,)>(|mut e| {
+        visited.push(e.id());
+        let alpha = e.get::<ComponentA>().unwrap().alpha;
+        if alpha < 2.0 {
+            e.add(ComponentB { beta: alpha as i32 });
+        } else if alpha == 3.0 {
+            e.remove::<ComponentA>();
+        }
+    });
+    debug_assert_eq!(visited, ids);
+
+    let with_a: Vec<_> = entity_list.iter::<(ComponentA,)>().map(|(i, _e)| i).collect();
+    let with_b: Vec<_> = entity_list.iter::<(ComponentB,)>().map(|(i, _e)| i).collect();
+    debug_assert_eq!(with_a, &ids[0..3]);
+    debug_assert_eq!(with_b, &ids[0..2]);
+    debug_assert_eq!(entity_list.get(ids[1]).unwrap().get::<ComponentB>(), Some(&ComponentB { beta: 1 }));
+}
+
+#[test]
+/// Tests that the entities touched during a safe iteration are marked as changed, and only them.
+fn iter_mut_safe_marks_changes() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+    entity_list.track_changes::<ComponentA>();
+    entity_list.track_changes::<ComponentB>();
+
+    let ids: Vec<_> = (0..3)
+        .map(|i| entity_list.insert(Entity::new(()).with(ComponentA { alpha: i as f32 })))
+        .collect();
+    entity_list.advance_tick();
+    let tick = entity_list.current_tick();
+
+    entity_list.iter_mut_safe::<(ComponentA,)>(|mut e| {
+        if e.id() == ids[0] {
+            e.get_mut::<ComponentA>().unwrap().alpha = 10.0;
+        } else if e.id() == ids[1] {
+            e.add(ComponentB { beta: 1 });
+        }
+    });
+
+    let changed_a: Vec<_> = entity_list.iter_changed::<ComponentA>(tick).map(|(i, _e)| i).collect();
+    let changed_b: Vec<_> = entity_list.iter_changed::<ComponentB>(tick).map(|(i, _e)| i).collect();
+    debug_assert_eq!(changed_a, &ids[0..2]);
+    debug_assert_eq!(changed_b, &ids[1..2]);
+}
+
+#[test]
+fn get_mut_checked() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+    let id = entity_list.insert(Entity::new(()).with(ComponentA { alpha: 1.0 }));
+
+    // mutating the value of a component is fine
+    {
+        let mut e = entity_list.get_mut_checked(id).unwrap();
+        e.a.as_mut().unwrap().alpha = 2.0;
+        debug_assert_eq!(e.id(), id);
+    }
+    debug_assert_eq!(entity_list.get_component::<ComponentA>(id), Some(&ComponentA { alpha: 2.0 }));
+    // replacing a component by another one is fine as well
+    entity_list.get_mut_checked(id).unwrap().a = Some(Box::new(ComponentA { alpha: 3.0 }));
+    debug_assert_eq!(entity_list.iter::<(ComponentA,)>().count(), 1);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "were changed via get_mut_checked")]
+fn get_mut_checked_add_component() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+    let id = entity_list.insert(Entity::new(()).with(ComponentA { alpha: 1.0 }));
+
+    let mut e = entity_list.get_mut_checked(id).unwrap();
+    e.b = Some(Box::new(ComponentB { beta: 1 }));
+}
\ No newline at end of file
@@ -0,0 +1,165 @@
+use mobec::{
+    define_entity,
+    EntityBase,
+};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct P {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Speed {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct CollisionBox {
+    w: f32,
+    h: f32,
+}
+
+define_entity! {
+    #[derive(Debug)]
+    pub struct Entity {
+        props => {
+            pos: P,
+        },
+        components => {
+            speed => Speed,
+            collision_box => CollisionBox,
+        }
+    }
+}
+
+#[test]
+fn get_two_mut() {
+    let mut e = Entity::new((P { x: 0.0, y: 0.0 },))
+        .with(Speed { x: 1.0, y: 2.0 })
+        .with(CollisionBox { w: 1.0, h: 1.0 });
+
+    if let Some((speed, collision_box)) = e.get_two_mut::<Speed, CollisionBox>() {
+        speed.x *= 2.0;
+        collision_box.w += speed.x;
+    }
+
+    debug_assert_eq!(e.get::<Speed>(), Some(&Speed { x: 2.0, y: 2.0 }));
+    debug_assert_eq!(e.get::<CollisionBox>(), Some(&CollisionBox { w: 3.0, h: 1.0 }));
+
+    // the same component can't be borrowed twice
+    debug_assert!(e.get_two_mut::<Speed, Speed>().is_none());
+
+    let mut e = e.with_removed::<CollisionBox>();
+    debug_assert!(e.get_two_mut::<Speed, CollisionBox>().is_none());
+    debug_assert!(e.get_two_mut::<CollisionBox, Speed>().is_none());
+}
+
+#[test]
+fn ensure() {
+    let mut e = Entity::new((P { x: 3.0, y: 4.0 },));
+
+    let collision_box = e.ensure(|e: &Entity| CollisionBox { w: e.pos.x, h: e.pos.y });
+    debug_assert_eq!(*collision_box, CollisionBox { w: 3.0, h: 4.0 });
+    collision_box.w += 1.0;
+
+    // the component already exists, so it is not computed again
+    e.pos.x = 10.0;
+    let collision_box = e.ensure(|e: &Entity| CollisionBox { w: e.pos.x, h: e.pos.y });
+    debug_assert_eq!(*collision_box, CollisionBox { w: 4.0, h: 4.0 });
+}
+
+#[test]
+fn insert() {
+    let mut e = Entity::new((P { x: 0.0, y: 0.0 },));
+
+    debug_assert_eq!(e.insert(Speed { x: 1.0, y: 1.0 }), None);
+    debug_assert_eq!(e.insert(Speed { x: 2.0, y: 2.0 }), Some(Box::new(Speed { x: 1.0, y: 1.0 })));
+    debug_assert_eq!(e.get::<Speed>(), Some(&Speed { x: 2.0, y: 2.0 }));
+}
+
+#[test]
+fn component_name() {
+    use mobec::Component;
+
+    debug_assert_eq!(<Speed as Component<Entity>>::INDEX, 0);
+    debug_assert_eq!(<CollisionBox as Component<Entity>>::INDEX, 1);
+    debug_assert_eq!(Entity::component_name(<Speed as Component<Entity>>::INDEX), Some("speed"));
+    debug_assert_eq!(Entity::component_name(<CollisionBox as Component<Entity>>::INDEX), Some("collision_box"));
+    debug_assert_eq!(Entity::component_name(2), None);
+}
+
+#[test]
+fn component_index_of() {
+    use std::any::TypeId;
+    use mobec::Component;
+
+    debug_assert_eq!(Entity::component_index_of(TypeId::of::<Speed>()), Some(<Speed as Component<Entity>>::INDEX));
+    debug_assert_eq!(Entity::component_index_of(TypeId::of::<CollisionBox>()), Some(<CollisionBox as Component<Entity>>::INDEX));
+    // props are not components
+    debug_assert_eq!(Entity::component_index_of(TypeId::of::<P>()), None);
+}
+
+#[test]
+fn get_all() {
+    let e = Entity::new((P { x: 0.0, y: 0.0 },));
+    debug_assert_eq!(e.get_all::<(Speed, CollisionBox)>(), (None, None));
+
+    let e = e.with(Speed { x: 1.0, y: 2.0 });
+    debug_assert_eq!(e.get_all::<(Speed, CollisionBox)>(), (Some(&Speed { x: 1.0, y: 2.0 }), None));
+    debug_assert_eq!(e.get_all::<(CollisionBox, Speed)>(), (None, Some(&Speed { x: 1.0, y: 2.0 })));
+
+    let e = e.with(CollisionBox { w: 3.0, h: 4.0 });
+    let (speed, collision_box) = e.get_all::<(Speed, CollisionBox)>();
+    debug_assert_eq!(speed, Some(&Speed { x: 1.0, y: 2.0 }));
+    debug_assert_eq!(collision_box, Some(&CollisionBox { w: 3.0, h: 4.0 }));
+    debug_assert_eq!(e.get_all::<(CollisionBox,)>(), (Some(&CollisionBox { w: 3.0, h: 4.0 }),));
+}
+
+
+#[test]
+fn missing_components() {
+    use mobec::ComponentMask;
+
+    let target: ComponentMask<Entity> = ComponentMask::new().with::<Speed>().with::<CollisionBox>();
+    let mut e = Entity::new((P { x: 0.0, y: 0.0 },)).with(Speed { x: 1.0, y: 2.0 });
+
+    let missing = e.missing_components(&target);
+    debug_assert_eq!(missing, ComponentMask::new().with::<CollisionBox>());
+    debug_assert!(missing.contains::<CollisionBox>());
+    debug_assert!(!missing.contains::<Speed>());
+    debug_assert_eq!(missing.len(), 1);
+    debug_assert_eq!(format!("{:?}", missing), "{\"collision_box\"}");
+
+    e.add(CollisionBox { w: 1.0, h: 1.0 });
+    debug_assert!(e.missing_components(&target).is_empty());
+    debug_assert_eq!(e.component_mask(), target);
+}
+
+mod no_props {
+    use mobec::define_entity;
+    use super::Speed;
+
+    define_entity! {
+        #[derive(Debug)]
+        pub struct Entity {
+            props => {},
+            components => {
+                speed => Speed,
+            }
+        }
+    }
+}
+
+#[test]
+fn new0_new1() {
+    let e = Entity::new1(P { x: 1.0, y: 2.0 });
+    debug_assert_eq!(e.pos, P { x: 1.0, y: 2.0 });
+    debug_assert!(!e.has::<Speed>());
+    debug_assert_eq!(e.pos, Entity::new((P { x: 1.0, y: 2.0 },)).pos);
+
+    let e = no_props::Entity::new0().with(Speed { x: 1.0, y: 0.0 });
+    debug_assert_eq!(e.get::<Speed>(), Some(&Speed { x: 1.0, y: 0.0 }));
+    debug_assert!(!no_props::Entity::new0().has::<Speed>());
+}
\ No newline at end of file
@@ -0,0 +1,1263 @@
+use mobec::{
+    define_entity,
+    EntityList,
+    EntityBase,
+    EntityId,
+    EntityListConfig,
+    IdReplayError,
+};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ComponentA {
+    alpha: f32,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ComponentB {
+    beta: i32,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct AgeProp {
+    age: u32,
+}
+
+define_entity! {
+    #[derive(Debug)]
+    pub struct Entity {
+        props => {
+            age: AgeProp,
+        },
+        components => {
+            a => ComponentA,
+            b => ComponentB,
+        }
+    }
+}
+
+#[test]
+fn cap() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    let mut ids = Vec::new();
+    for i in 0..10 {
+        ids.push(entity_list.insert(
+            Entity::new((AgeProp { age: i },))
+                .with(ComponentA { alpha: i as f32 })
+        ));
+    }
+
+    // nothing to do under the limit
+    entity_list.cap(20, |_| true);
+    debug_assert_eq!(entity_list.len(), 10);
+
+    // old entities are removed first
+    entity_list.cap(7, |e| e.age.age >= 5);
+    debug_assert_eq!(entity_list.len(), 7);
+    let remaining: Vec<_> = entity_list.iter::<(ComponentA,)>().map(|(i, _e)| i).collect();
+    debug_assert_eq!(remaining, &[ids[0], ids[1], ids[2], ids[3], ids[4], ids[8], ids[9]]);
+
+    // not enough victims, so other entities get removed as well
+    entity_list.cap(3, |e| e.age.age == 9);
+    debug_assert_eq!(entity_list.len(), 3);
+    let remaining: Vec<_> = entity_list.iter::<(ComponentA,)>().map(|(i, _e)| i).collect();
+    debug_assert_eq!(remaining, &[ids[3], ids[4], ids[8]]);
+}
+
+#[test]
+fn get_or_insert_with() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    // no id: inserts
+    let (id_1, e) = entity_list.get_or_insert_with(None, || Entity::new((AgeProp { age: 1 },)));
+    e.age.age += 1;
+    debug_assert_eq!(entity_list.len(), 1);
+    debug_assert_eq!(entity_list.get(id_1).map(|e| e.age.age), Some(2));
+
+    // live id: returns the existing entity
+    let (id, e) = entity_list.get_or_insert_with(Some(id_1), || Entity::new((AgeProp { age: 10 },)));
+    debug_assert_eq!(id, id_1);
+    debug_assert_eq!(e.age.age, 2);
+    debug_assert_eq!(entity_list.len(), 1);
+
+    // stale id: inserts a fresh entity
+    entity_list.remove(id_1);
+    let (id_2, e) = entity_list.get_or_insert_with(Some(id_1), || {
+        Entity::new((AgeProp { age: 10 },)).with(ComponentA { alpha: 1.0 })
+    });
+    debug_assert_eq!(e.age.age, 10);
+    debug_assert_ne!(id_2, id_1);
+    debug_assert_eq!(entity_list.len(), 1);
+    let only_comp_a: Vec<_> = entity_list.iter::<(ComponentA,)>().map(|(i, _e)| i).collect();
+    debug_assert_eq!(only_comp_a, &[id_2]);
+}
+
+#[test]
+fn insert_mut() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    let (id_1, e) = entity_list.insert_mut(
+        Entity::new((AgeProp { age: 1 },))
+            .with(ComponentA { alpha: 1.0 })
+    );
+    e.age.age = 42;
+    e.mutate(|a: &mut ComponentA| a.alpha = 2.0);
+
+    debug_assert_eq!(entity_list.get(id_1).map(|e| e.age.age), Some(42));
+    let only_comp_a: Vec<_> = entity_list.iter::<(ComponentA,)>().map(|(i, e)| (i, *e.get::<ComponentA>().unwrap())).collect();
+    debug_assert_eq!(only_comp_a, &[(id_1, ComponentA { alpha: 2.0 })]);
+}
+
+#[test]
+fn contains_all() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    let id_1 = entity_list.insert(Entity::new((AgeProp { age: 1 },)));
+    let id_2 = entity_list.insert(Entity::new((AgeProp { age: 2 },)));
+    let id_3 = entity_list.insert(Entity::new((AgeProp { age: 3 },)));
+
+    debug_assert!(entity_list.contains_all(&[id_1, id_2, id_3]));
+    debug_assert!(entity_list.contains_all(&[]));
+
+    entity_list.remove(id_2);
+    debug_assert!(!entity_list.contains_all(&[id_1, id_2, id_3]));
+    debug_assert!(entity_list.contains_all(&[id_1, id_3]));
+}
+
+#[test]
+fn remove_query() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    let mut ids = Vec::new();
+    for i in 0..10 {
+        let e = Entity::new((AgeProp { age: i },))
+            .with(ComponentA { alpha: i as f32 });
+        if i % 3 == 0 {
+            ids.push(entity_list.insert(e.with(ComponentB { beta: 0 })));
+        } else {
+            ids.push(entity_list.insert(e));
+        }
+    }
+
+    debug_assert_eq!(entity_list.remove_query::<(ComponentB,)>(), 4);
+    debug_assert_eq!(entity_list.len(), 6);
+    debug_assert_eq!(entity_list.iter::<(ComponentB,)>().count(), 0);
+    let only_comp_a: Vec<_> = entity_list.iter::<(ComponentA,)>().map(|(i, _e)| i).collect();
+    debug_assert_eq!(only_comp_a, &[ids[1], ids[2], ids[4], ids[5], ids[7], ids[8]]);
+
+    debug_assert_eq!(entity_list.remove_query::<(ComponentA, ComponentB)>(), 0);
+}
+
+#[test]
+fn weak_entity() {
+    use mobec::WeakEntity;
+
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    let id_1 = entity_list.insert(Entity::new((AgeProp { age: 1 },)));
+    let weak: WeakEntity<Entity> = entity_list.weak(id_1);
+    debug_assert!(weak.is_alive(&entity_list));
+    debug_assert_eq!(weak.upgrade(&entity_list).map(|e| e.age.age), Some(1));
+
+    if let Some(e) = weak.upgrade_mut(&mut entity_list) {
+        e.age.age = 2;
+    }
+    debug_assert_eq!(weak.upgrade(&entity_list).map(|e| e.age.age), Some(2));
+
+    // the new entity re-uses the slot of the removed one, but the handle is still stale
+    entity_list.remove(id_1);
+    let id_2 = entity_list.insert(Entity::new((AgeProp { age: 3 },)));
+    debug_assert_eq!(id_1.into_raw_parts().0, id_2.into_raw_parts().0);
+    debug_assert!(!weak.is_alive(&entity_list));
+    debug_assert!(weak.upgrade(&entity_list).is_none());
+    debug_assert!(WeakEntity::<Entity>::from(id_2).is_alive(&entity_list));
+}
+
+impl mobec::ValidatableEntity for Entity {
+    fn validate(&self) -> Result<(), String> {
+        if self.has::<ComponentB>() && !self.has::<ComponentA>() {
+            Err(String::from("having ComponentB requires having ComponentA"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[test]
+fn validate_all() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    entity_list.insert(
+        Entity::new((AgeProp { age: 1 },))
+            .with(ComponentA { alpha: 1.0 })
+            .with(ComponentB { beta: 1 })
+    );
+    entity_list.insert(Entity::new((AgeProp { age: 2 },)));
+    debug_assert_eq!(entity_list.validate_all(), Ok(()));
+
+    let id_3 = entity_list.insert(
+        Entity::new((AgeProp { age: 3 },))
+            .with(ComponentB { beta: 1 })
+    );
+    debug_assert_eq!(entity_list.validate_all(), Err((id_3, String::from("having ComponentB requires having ComponentA"))));
+}
+
+#[test]
+fn partition() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    for i in 0..10 {
+        let e = Entity::new((AgeProp { age: i },));
+        let e = if i % 2 == 0 { e.with(ComponentA { alpha: i as f32 }) } else { e };
+        let e = if i % 3 == 0 { e.with(ComponentB { beta: i as i32 }) } else { e };
+        entity_list.insert(e);
+    }
+
+    let (young, old) = entity_list.partition(|e| e.age.age < 5);
+    debug_assert_eq!(young.len(), 5);
+    debug_assert_eq!(old.len(), 5);
+
+    let young_a: Vec<_> = young.iter::<(ComponentA,)>().map(|(_i, e)| e.age.age).collect();
+    let young_b: Vec<_> = young.iter::<(ComponentB,)>().map(|(_i, e)| e.age.age).collect();
+    let old_a: Vec<_> = old.iter::<(ComponentA,)>().map(|(_i, e)| e.age.age).collect();
+    let old_ab: Vec<_> = old.iter::<(ComponentA, ComponentB)>().map(|(_i, e)| e.age.age).collect();
+    debug_assert_eq!(young_a, &[0, 2, 4]);
+    debug_assert_eq!(young_b, &[0, 3]);
+    debug_assert_eq!(old_a, &[6, 8]);
+    debug_assert_eq!(old_ab, &[6]);
+}
+
+#[test]
+fn clone_matching() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    for i in 0..10 {
+        let e = Entity::new((AgeProp { age: i },));
+        let e = if i % 2 == 0 { e.with(ComponentA { alpha: i as f32 }) } else { e };
+        let e = if i % 3 == 0 { e.with(ComponentB { beta: i as i32 }) } else { e };
+        entity_list.insert(e);
+    }
+
+    let only_a = entity_list.clone_matching::<(ComponentA,)>();
+    debug_assert_eq!(only_a.len(), 5);
+    debug_assert_eq!(entity_list.len(), 10);
+
+    let all: Vec<_> = only_a.iter_all().map(|(_i, e)| e.age.age).collect();
+    let with_a: Vec<_> = only_a.iter::<(ComponentA,)>().map(|(_i, e)| e.age.age).collect();
+    let with_b: Vec<_> = only_a.iter::<(ComponentB,)>().map(|(_i, e)| e.age.age).collect();
+    debug_assert_eq!(all, &[0, 2, 4, 6, 8]);
+    debug_assert_eq!(with_a, &[0, 2, 4, 6, 8]);
+    debug_assert_eq!(with_b, &[0, 6]);
+}
+
+#[test]
+fn apply_component_updates() {
+    use std::collections::HashMap;
+
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    let mut ids = Vec::new();
+    for i in 0..6 {
+        let e = Entity::new((AgeProp { age: i },));
+        let e = if i % 2 == 0 { e.with(ComponentB { beta: 0 }) } else { e };
+        ids.push(entity_list.insert(e));
+    }
+    entity_list.remove(ids[5]);
+
+    let mut updates: HashMap<EntityId, ComponentB> = HashMap::new();
+    updates.insert(ids[0], ComponentB { beta: 10 });
+    updates.insert(ids[1], ComponentB { beta: 11 });
+    updates.insert(ids[3], ComponentB { beta: 13 });
+    updates.insert(ids[5], ComponentB { beta: 15 });
+    entity_list.apply_component_updates(updates);
+
+    let with_b: Vec<_> = entity_list.iter::<(ComponentB,)>().map(|(i, e)| (i, e.get::<ComponentB>().unwrap().beta)).collect();
+    debug_assert_eq!(with_b, &[(ids[0], 10), (ids[1], 11), (ids[2], 0), (ids[3], 13), (ids[4], 0)]);
+    debug_assert!(!entity_list.contains(ids[5]));
+}
+
+#[test]
+fn zip_apply() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    let ids: Vec<_> = (0..5).map(|i| entity_list.insert(Entity::new((AgeProp { age: i },)))).collect();
+    entity_list.remove(ids[1]);
+    entity_list.remove(ids[3]);
+
+    // computed somewhere else, by a system which doesn't know entities 1 and 3 are gone
+    let results: Vec<(EntityId, ComponentA)> = ids.iter()
+        .enumerate()
+        .map(|(i, id)| (*id, ComponentA { alpha: i as f32 * 2.0 }))
+        .collect();
+
+    let stale = entity_list.zip_apply_checked(&results);
+    debug_assert_eq!(stale, &[ids[1], ids[3]]);
+
+    let with_a: Vec<_> = entity_list.iter::<(ComponentA,)>().map(|(i, e)| (i, e.get::<ComponentA>().unwrap().alpha)).collect();
+    debug_assert_eq!(with_a, &[(ids[0], 0.0), (ids[2], 4.0), (ids[4], 8.0)]);
+
+    let results = [(ids[0], ComponentA { alpha: -1.0 }), (ids[1], ComponentA { alpha: -1.0 })];
+    entity_list.zip_apply(&results);
+    debug_assert_eq!(entity_list.get_component::<ComponentA>(ids[0]), Some(&ComponentA { alpha: -1.0 }));
+    debug_assert_eq!(entity_list.len(), 3);
+}
+
+#[test]
+fn refresh_component() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    let ids: Vec<_> = (0..4)
+        .map(|i| entity_list.insert(Entity::new((AgeProp { age: i },)).with(ComponentA { alpha: 0.0 })))
+        .collect();
+
+    // edit both components out-of-band
+    entity_list.get_mut(ids[1]).unwrap().b = Some(Box::new(ComponentB { beta: 1 }));
+    entity_list.get_mut(ids[3]).unwrap().b = Some(Box::new(ComponentB { beta: 3 }));
+    entity_list.get_mut(ids[2]).unwrap().a = None;
+    debug_assert_eq!(entity_list.iter::<(ComponentB,)>().count(), 0);
+
+    entity_list.refresh_component::<ComponentB>();
+
+    let with_b: Vec<_> = entity_list.iter::<(ComponentB,)>().map(|(i, _e)| i).collect();
+    debug_assert_eq!(with_b, &[ids[1], ids[3]]);
+    // the bitset of A was not refreshed, so it still (wrongly) lists ids[2]
+    let with_a: Vec<_> = entity_list.iter::<(ComponentA,)>().map(|(i, _e)| i).collect();
+    debug_assert_eq!(with_a, ids);
+}
+
+#[test]
+fn from_iter_with_ids() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    // churn a bit, so that ids have various generations and the slots are not in order
+    let mut ids = Vec::new();
+    for i in 0..20 {
+        let e = Entity::new((AgeProp { age: i },));
+        let e = if i % 2 == 0 { e.with(ComponentA { alpha: i as f32 }) } else { e };
+        ids.push(entity_list.insert(e));
+        if i % 3 == 0 {
+            entity_list.remove(ids[i as usize / 2]);
+        }
+        if i % 7 == 0 {
+            let id = ids[i as usize];
+            entity_list.remove(id);
+        }
+    }
+    for i in 20..25 {
+        ids.push(entity_list.insert(Entity::new((AgeProp { age: i },)).with(ComponentB { beta: i as i32 })));
+    }
+
+    // the order of the pairs does not matter
+    let mut pairs: Vec<(EntityId, Entity)> = entity_list.iter_all().map(|(id, e)| (id, e.clone())).collect();
+    pairs.reverse();
+    let loaded = EntityList::from_iter_with_ids(pairs).unwrap();
+
+    debug_assert_eq!(loaded.len(), entity_list.len());
+    for id in &ids {
+        match (entity_list.get(*id), loaded.get(*id)) {
+            (Some(e), Some(loaded_e)) => {
+                debug_assert_eq!(e.age, loaded_e.age);
+                debug_assert_eq!(e.get::<ComponentA>(), loaded_e.get::<ComponentA>());
+                debug_assert_eq!(e.get::<ComponentB>(), loaded_e.get::<ComponentB>());
+            },
+            (None, None) => {},
+            _ => panic!("id {:?} does not resolve the same way after loading", id),
+        }
+    }
+    let query = |l: &EntityList<Entity>| l.iter::<(ComponentA,)>().map(|(i, _e)| i).collect::<Vec<_>>();
+    debug_assert_eq!(query(&loaded), query(&entity_list));
+
+    // new ids never collide with the loaded ones
+    let mut loaded = loaded;
+    for i in 0..30 {
+        let id = loaded.insert(Entity::new((AgeProp { age: 100 + i },)));
+        debug_assert!(!ids.contains(&id));
+    }
+
+    // a slot that stays free while other slots are removed and re-used
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+    let ids: Vec<EntityId> = (0..4).map(|i| entity_list.insert(Entity::new((AgeProp { age: i },)))).collect();
+    entity_list.remove(ids[1]);
+    entity_list.remove(ids[0]);
+    entity_list.insert(Entity::new((AgeProp { age: 4 },)));
+    entity_list.remove(ids[2]);
+    entity_list.insert(Entity::new((AgeProp { age: 5 },)));
+    entity_list.insert(Entity::new((AgeProp { age: 6 },)));
+    let pairs: Vec<(EntityId, Entity)> = entity_list.iter_all().map(|(id, e)| (id, e.clone())).collect();
+    let loaded = EntityList::from_iter_with_ids(pairs.clone()).unwrap();
+    debug_assert_eq!(loaded.iter_all().map(|(id, e)| (id, e.age)).collect::<Vec<_>>(),
+        pairs.iter().map(|(id, e)| (*id, e.age)).collect::<Vec<_>>());
+
+    let empty: EntityList<Entity> = EntityList::from_iter_with_ids(Vec::new()).unwrap();
+    debug_assert_eq!(empty.len(), 0);
+}
+
+#[test]
+fn from_iter_with_ids_gaps_and_generations() {
+    let entity = |age| Entity::new((AgeProp { age },));
+    let check = |pairs: Vec<(EntityId, Entity)>| {
+        let mut loaded = EntityList::from_iter_with_ids(pairs.clone()).unwrap();
+        debug_assert_eq!(loaded.len(), pairs.len());
+        for (id, e) in &pairs {
+            debug_assert_eq!(loaded.get(*id).map(|loaded_e| loaded_e.age), Some(e.age));
+        }
+        for i in 0..10 {
+            let id = loaded.insert(entity(100 + i));
+            debug_assert!(pairs.iter().all(|(loaded_id, _e)| *loaded_id != id));
+        }
+    };
+
+    // free slots between the entities, and before the first one
+    check(vec![
+        (EntityId::from_raw_parts(1, 0), entity(1)),
+        (EntityId::from_raw_parts(4, 0), entity(4)),
+        (EntityId::from_raw_parts(9, 0), entity(9)),
+    ]);
+
+    // generations far above the number of slots, each one takes as many removals to reach
+    check(vec![(EntityId::from_raw_parts(0, 100_000), entity(0))]);
+    check(vec![
+        (EntityId::from_raw_parts(2, 3), entity(2)),
+        (EntityId::from_raw_parts(5, 50_000), entity(5)),
+        (EntityId::from_raw_parts(0, 100_000), entity(0)),
+    ]);
+}
+
+#[test]
+fn from_iter_with_ids_errors() {
+    let entity = || Entity::new((AgeProp { age: 0 },));
+
+    let pairs = vec![(EntityId::from_raw_parts(0, 0), entity()), (EntityId::from_raw_parts(0, 1), entity())];
+    debug_assert_eq!(EntityList::from_iter_with_ids(pairs).err(), Some(IdReplayError::DuplicateIndex(0)));
+
+    let pairs = vec![(EntityId::from_raw_parts(mobec::MAX_ENTITY_SLOTS, 0), entity())];
+    debug_assert_eq!(EntityList::from_iter_with_ids(pairs).err(), Some(IdReplayError::IndexTooLarge(mobec::MAX_ENTITY_SLOTS)));
+
+    // slot 2 was used at generation 0, so slots 0 and 1 were as well, and re-using both of them
+    // takes two removals, which moves the generation to 2
+    let pairs = vec![
+        (EntityId::from_raw_parts(2, 0), entity()),
+        (EntityId::from_raw_parts(0, 1), entity()),
+        (EntityId::from_raw_parts(1, 1), entity()),
+    ];
+    let err = EntityList::from_iter_with_ids(pairs).unwrap_err();
+    debug_assert!(matches!(err, IdReplayError::Unreproducible(_)));
+    debug_assert!(err.to_string().contains("cannot be reproduced"));
+}
+
+#[test]
+fn recycle() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    let id_1 = entity_list.insert(Entity::new((AgeProp { age: 1 },)).with(ComponentA { alpha: 1.0 }).with(ComponentB { beta: 1 }));
+    let id_2 = entity_list.insert(Entity::new((AgeProp { age: 2 },)).with(ComponentA { alpha: 2.0 }));
+
+    entity_list.recycle(id_1);
+    let e = entity_list.get(id_1).unwrap();
+    debug_assert_eq!(e.age.age, 1);
+    debug_assert!(e.get::<ComponentA>().is_none());
+    debug_assert!(e.get::<ComponentB>().is_none());
+    debug_assert_eq!(entity_list.iter::<(ComponentA,)>().map(|(i, _e)| i).collect::<Vec<_>>(), &[id_2]);
+    debug_assert_eq!(entity_list.iter::<(ComponentB,)>().count(), 0);
+
+    entity_list.remove(id_2);
+    entity_list.recycle(id_2);
+    debug_assert_eq!(entity_list.len(), 1);
+}
+
+#[test]
+fn generation_high_water() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+    debug_assert_eq!(entity_list.generation_high_water(), 0);
+
+    let kept = entity_list.insert(Entity::new((AgeProp { age: 0 },)));
+    let mut last_high_water = entity_list.generation_high_water();
+    for i in 0..10 {
+        // the same slot is reused every time, with a new generation
+        let id = entity_list.insert(Entity::new((AgeProp { age: i },)));
+        let high_water = entity_list.generation_high_water();
+        debug_assert!(high_water >= last_high_water);
+        debug_assert_eq!(high_water, id.into_raw_parts().1);
+        last_high_water = high_water;
+        entity_list.remove(id);
+    }
+    debug_assert!(last_high_water >= 9);
+
+    // only live entities count
+    debug_assert_eq!(entity_list.generation_high_water(), kept.into_raw_parts().1);
+}
+
+#[test]
+fn try_insert() {
+    use hibitset::{BitSet, BitSetLike};
+    use mobec::MAX_ENTITY_SLOTS;
+
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+    let id = entity_list.try_insert(Entity::new((AgeProp { age: 1 },)).with(ComponentA { alpha: 1.0 }), MAX_ENTITY_SLOTS).ok().unwrap();
+    debug_assert_eq!(entity_list.iter::<(ComponentA,)>().map(|(i, _e)| i).collect::<Vec<_>>(), &[id]);
+
+    // filling the list up to the limit would take too much memory, but the limit must be the one
+    // of the bitsets: the last slot fits, the next one is silently lost.
+    let mut bitset = BitSet::new();
+    bitset.add(MAX_ENTITY_SLOTS as u32 - 1);
+    debug_assert_eq!((&bitset).iter().collect::<Vec<_>>(), &[MAX_ENTITY_SLOTS as u32 - 1]);
+    let mut bitset = BitSet::new();
+    bitset.add(MAX_ENTITY_SLOTS as u32);
+    debug_assert_eq!((&bitset).iter().count(), 0);
+}
+
+#[test]
+fn try_insert_max_len() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    // under the limit
+    let id_1 = entity_list.try_insert(Entity::new((AgeProp { age: 1 },)), 2).ok().unwrap();
+    let id_2 = entity_list.try_insert(Entity::new((AgeProp { age: 2 },)).with(ComponentA { alpha: 2.0 }), 2).ok().unwrap();
+    debug_assert_eq!(entity_list.len(), 2);
+
+    // at the limit, the entity is given back untouched
+    let e = entity_list.try_insert(Entity::new((AgeProp { age: 3 },)).with(ComponentA { alpha: 3.0 }), 2).err().unwrap();
+    debug_assert_eq!(e.age.age, 3);
+    debug_assert_eq!(entity_list.len(), 2);
+    debug_assert_eq!(entity_list.iter::<(ComponentA,)>().map(|(i, _e)| i).collect::<Vec<_>>(), &[id_2]);
+
+    // removing an entity frees room in the budget
+    entity_list.remove(id_1);
+    debug_assert!(entity_list.try_insert(e, 2).is_ok());
+    debug_assert_eq!(entity_list.iter::<(ComponentA,)>().count(), 2);
+}
+
+#[test]
+fn replace() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    let id_1 = entity_list.insert(Entity::new((AgeProp { age: 1 },)).with(ComponentA { alpha: 1.0 }));
+    let id_2 = entity_list.insert(Entity::new((AgeProp { age: 2 },)).with(ComponentA { alpha: 2.0 }));
+
+    let old = entity_list.replace(id_1, Entity::new((AgeProp { age: 10 },)).with(ComponentB { beta: 10 })).unwrap();
+    debug_assert_eq!(old.age.age, 1);
+    debug_assert_eq!(old.get::<ComponentA>().unwrap().alpha, 1.0);
+
+    let e = entity_list.get(id_1).unwrap();
+    debug_assert_eq!(e.age.age, 10);
+    debug_assert!(e.get::<ComponentA>().is_none());
+    debug_assert_eq!(entity_list.iter::<(ComponentA,)>().map(|(i, _e)| i).collect::<Vec<_>>(), &[id_2]);
+    debug_assert_eq!(entity_list.iter::<(ComponentB,)>().map(|(i, _e)| i).collect::<Vec<_>>(), &[id_1]);
+
+    entity_list.remove(id_2);
+    debug_assert!(entity_list.replace(id_2, Entity::new((AgeProp { age: 20 },))).is_none());
+    debug_assert_eq!(entity_list.len(), 1);
+}
+
+#[test]
+fn swap() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    let id_1 = entity_list.insert(Entity::new((AgeProp { age: 1 },)).with(ComponentA { alpha: 1.0 }));
+    let id_2 = entity_list.insert(Entity::new((AgeProp { age: 2 },)).with(ComponentB { beta: 2 }));
+    let id_3 = entity_list.insert(Entity::new((AgeProp { age: 3 },)).with(ComponentA { alpha: 3.0 }));
+
+    debug_assert!(entity_list.swap(id_1, id_2));
+    debug_assert_eq!(entity_list.get(id_1).unwrap().age.age, 2);
+    debug_assert_eq!(entity_list.get(id_2).unwrap().age.age, 1);
+    debug_assert_eq!(entity_list.iter::<(ComponentA,)>().map(|(i, _e)| i).collect::<Vec<_>>(), &[id_2, id_3]);
+    debug_assert_eq!(entity_list.iter::<(ComponentB,)>().map(|(i, _e)| i).collect::<Vec<_>>(), &[id_1]);
+
+    debug_assert!(!entity_list.swap(id_1, id_1));
+    entity_list.remove(id_3);
+    debug_assert!(!entity_list.swap(id_1, id_3));
+    debug_assert!(!entity_list.swap(id_3, id_2));
+    debug_assert_eq!(entity_list.get(id_1).unwrap().age.age, 2);
+    debug_assert_eq!(entity_list.iter::<(ComponentA,)>().map(|(i, _e)| i).collect::<Vec<_>>(), &[id_2]);
+}
+
+#[test]
+/// Tests that regenerated bitsets do not depend on the order components were added in.
+fn regenerated_bitsets() {
+    use generational_arena::Arena;
+
+    let build = |i: i32, b_first: bool| {
+        let mut e = Entity::new((AgeProp { age: i as u32 },));
+        if b_first && i % 3 == 0 {
+            e.add(ComponentB { beta: i });
+        }
+        if i % 2 == 0 {
+            e.add(ComponentA { alpha: i as f32 });
+        }
+        if !b_first && i % 3 == 0 {
+            e.add(ComponentB { beta: i });
+        }
+        e
+    };
+
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+    let mut arena_a_first: Arena<Entity> = Arena::new();
+    let mut arena_b_first: Arena<Entity> = Arena::new();
+    for i in 0..20 {
+        entity_list.insert(build(i, false));
+        arena_a_first.insert(build(i, false));
+        arena_b_first.insert(build(i, true));
+    }
+
+    let with_a_and_b = |entity_list: &EntityList<Entity>| {
+        (
+            entity_list.iter::<(ComponentA,)>().map(|(i, _e)| i).collect::<Vec<_>>(),
+            entity_list.iter::<(ComponentB,)>().map(|(i, _e)| i).collect::<Vec<_>>(),
+        )
+    };
+    let expected = with_a_and_b(&entity_list);
+    debug_assert_eq!(expected.0.len(), 10);
+    debug_assert_eq!(expected.1.len(), 7);
+    debug_assert_eq!(with_a_and_b(&EntityList::from_arena(arena_a_first)), expected);
+    debug_assert_eq!(with_a_and_b(&EntityList::from_arena(arena_b_first)), expected);
+}
+
+#[test]
+/// Tests that ids past the bitset limit never alias the entities at the start of the list.
+fn out_of_range_ids() {
+    use generational_arena::Index;
+    use mobec::MAX_ENTITY_SLOTS;
+
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+    let id = entity_list.insert(Entity::new((AgeProp { age: 1 },)).with(ComponentA { alpha: 1.0 }));
+
+    // truncated to u32, or wrapped around in the bitsets, these would be index 0
+    for fake_index in [MAX_ENTITY_SLOTS, u32::MAX as usize + 1].iter() {
+        let fake_id = Index::from_raw_parts(*fake_index, id.into_raw_parts().1);
+        debug_assert!(!entity_list.contains(fake_id));
+        debug_assert!(entity_list.remove(fake_id).is_none());
+        debug_assert!(entity_list.remove_component_for_entity::<ComponentA>(fake_id).is_none());
+        entity_list.refresh(fake_id);
+        entity_list.untag::<ComponentA>(fake_id);
+    }
+
+    let with_a: Vec<_> = entity_list.iter::<(ComponentA,)>().map(|(i, _e)| i).collect();
+    debug_assert_eq!(with_a, &[id]);
+}
+
+#[test]
+fn bitset_index() {
+    use mobec::{bitset_index, MAX_ENTITY_SLOTS};
+
+    debug_assert_eq!(bitset_index(0), 0);
+    debug_assert_eq!(bitset_index(MAX_ENTITY_SLOTS - 1), MAX_ENTITY_SLOTS as u32 - 1);
+}
+
+#[test]
+#[should_panic(expected = "does not fit in the bitsets")]
+fn bitset_index_out_of_range() {
+    use mobec::{bitset_index, MAX_ENTITY_SLOTS};
+
+    // truncated, this would be the index of another entity
+    bitset_index(MAX_ENTITY_SLOTS);
+}
+
+#[test]
+fn merge_component_from() {
+    use std::collections::HashMap;
+
+    let mut local: EntityList<Entity> = EntityList::new();
+    let mut remote: EntityList<Entity> = EntityList::new();
+
+    let local_ids: Vec<_> = (0..4).map(|i| local.insert(Entity::new((AgeProp { age: i },)))).collect();
+    // the remote list has its entities in another order, and one more entity.
+    let remote_ids: Vec<_> = (0..5).rev()
+        .map(|i| {
+            let e = Entity::new((AgeProp { age: i },));
+            let e = if i != 2 { e.with(ComponentA { alpha: i as f32 * 10.0 }) } else { e };
+            remote.insert(e)
+        })
+        .collect();
+    local.remove(local_ids[3]);
+
+    let mut id_map: HashMap<EntityId, EntityId> = HashMap::new();
+    for i in 0..4 {
+        id_map.insert(remote_ids[4 - i], local_ids[i]);
+    }
+
+    local.merge_component_from::<ComponentA>(&remote, id_map);
+
+    let with_a: Vec<_> = local.iter::<(ComponentA,)>().map(|(i, e)| (i, e.age.age, e.get::<ComponentA>().unwrap().alpha)).collect();
+    // 2 has no `ComponentA` remotely, 3 was removed locally
+    debug_assert_eq!(with_a, &[(local_ids[0], 0, 0.0), (local_ids[1], 1, 10.0)]);
+    debug_assert_eq!(remote.iter::<(ComponentA,)>().count(), 4);
+}
+
+#[test]
+fn collect_component() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    for i in 0..10 {
+        let e = Entity::new((AgeProp { age: i },));
+        let e = if i % 3 == 0 { e.with(ComponentB { beta: i as i32 }) } else { e };
+        entity_list.insert(e);
+    }
+
+    let collected = entity_list.collect_component::<ComponentB>();
+    let manual: Vec<_> = entity_list.iter_all()
+        .filter_map(|(i, e)| e.get::<ComponentB>().map(|b| (i, *b)))
+        .collect();
+    debug_assert_eq!(collected.len(), 4);
+    debug_assert_eq!(collected, manual);
+    debug_assert!(entity_list.collect_component::<ComponentA>().is_empty());
+}
+
+#[test]
+fn clone_entities_only() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    let mut ids = Vec::new();
+    for i in 0..10 {
+        let e = Entity::new((AgeProp { age: i },));
+        let e = if i % 2 == 0 { e.with(ComponentA { alpha: i as f32 }) } else { e };
+        let e = if i % 3 == 0 { e.with(ComponentB { beta: i as i32 }) } else { e };
+        ids.push(entity_list.insert(e));
+    }
+    entity_list.remove(ids[4]);
+
+    let full = entity_list.clone();
+    let light = entity_list.clone_entities_only();
+
+    let ages = |l: &EntityList<Entity>| -> (Vec<_>, Vec<_>, Vec<_>, Vec<_>) {(
+        l.iter_all().map(|(i, e)| (i, e.age.age)).collect(),
+        l.iter::<(ComponentA,)>().map(|(i, e)| (i, e.age.age)).collect(),
+        l.iter::<(ComponentB,)>().map(|(i, e)| (i, e.age.age)).collect(),
+        l.iter::<(ComponentA, ComponentB)>().map(|(i, e)| (i, e.age.age)).collect(),
+    )};
+    debug_assert_eq!(ages(&full), ages(&light));
+    debug_assert_eq!(light.len(), 9);
+    debug_assert!(!light.contains(ids[4]));
+}
+
+#[test]
+fn drain_query() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    let mut ids = Vec::new();
+    for i in 0..6 {
+        let e = Entity::new((AgeProp { age: i },))
+            .with(ComponentA { alpha: i as f32 });
+        let e = if i % 2 == 0 { e.with(ComponentB { beta: i as i32 }) } else { e };
+        ids.push(entity_list.insert(e));
+    }
+
+    let drained: Vec<_> = entity_list.drain_query::<(ComponentB,)>().map(|(i, e)| (i, e.age.age)).collect();
+    debug_assert_eq!(drained, &[(ids[0], 0), (ids[2], 2), (ids[4], 4)]);
+
+    debug_assert_eq!(entity_list.len(), 3);
+    debug_assert!(!entity_list.contains(ids[0]));
+    debug_assert_eq!(entity_list.iter::<(ComponentB,)>().count(), 0);
+    let only_comp_a: Vec<_> = entity_list.iter::<(ComponentA,)>().map(|(i, _e)| i).collect();
+    debug_assert_eq!(only_comp_a, &[ids[1], ids[3], ids[5]]);
+
+    // entities are removed even if the iterator is dropped right away
+    let _ = entity_list.drain_query::<(ComponentA,)>();
+    debug_assert_eq!(entity_list.len(), 0);
+}
+
+#[test]
+fn get_component() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    let id_1 = entity_list.insert(
+        Entity::new((AgeProp { age: 1 },))
+            .with(ComponentA { alpha: 1.0 })
+    );
+
+    debug_assert_eq!(entity_list.get_component::<ComponentA>(id_1), Some(&ComponentA { alpha: 1.0 }));
+    debug_assert_eq!(entity_list.get_component::<ComponentB>(id_1), None);
+
+    if let Some(a) = entity_list.get_component_mut::<ComponentA>(id_1) {
+        a.alpha = 2.0;
+    }
+    debug_assert_eq!(entity_list.get_component::<ComponentA>(id_1), Some(&ComponentA { alpha: 2.0 }));
+    debug_assert_eq!(entity_list.get_component_mut::<ComponentB>(id_1), None);
+
+    entity_list.remove(id_1);
+    debug_assert_eq!(entity_list.get_component::<ComponentA>(id_1), None);
+    debug_assert_eq!(entity_list.get_component_mut::<ComponentA>(id_1), None);
+}
+
+#[test]
+fn get_component_any() {
+    use std::any::TypeId;
+
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    let id_1 = entity_list.insert(
+        Entity::new((AgeProp { age: 1 },))
+            .with(ComponentA { alpha: 1.0 })
+    );
+
+    let a = entity_list.get_component_any(id_1, TypeId::of::<ComponentA>());
+    debug_assert_eq!(a.and_then(|a| a.downcast_ref::<ComponentA>()), Some(&ComponentA { alpha: 1.0 }));
+    debug_assert!(entity_list.get_component_any(id_1, TypeId::of::<ComponentB>()).is_none());
+    // not a component of this entity
+    debug_assert!(entity_list.get_component_any(id_1, TypeId::of::<AgeProp>()).is_none());
+
+    entity_list.remove(id_1);
+    debug_assert!(entity_list.get_component_any(id_1, TypeId::of::<ComponentA>()).is_none());
+}
+
+#[test]
+fn get_component_any_mut() {
+    use std::any::TypeId;
+    use mobec::Tick;
+
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+    entity_list.track_changes::<ComponentA>();
+
+    let id_1 = entity_list.insert(
+        Entity::new((AgeProp { age: 1 },))
+            .with(ComponentA { alpha: 1.0 })
+    );
+    let id_2 = entity_list.insert(Entity::new((AgeProp { age: 2 },)).with(ComponentA { alpha: 2.0 }));
+    entity_list.advance_tick();
+
+    if let Some(a) = entity_list.get_component_any_mut(id_1, TypeId::of::<ComponentA>()) {
+        a.downcast_mut::<ComponentA>().unwrap().alpha = 3.0;
+    }
+    debug_assert_eq!(entity_list.get_component::<ComponentA>(id_1), Some(&ComponentA { alpha: 3.0 }));
+    debug_assert_eq!(entity_list.get_component::<ComponentA>(id_2), Some(&ComponentA { alpha: 2.0 }));
+    let changed: Vec<_> = entity_list.iter_changed::<ComponentA>(Tick(1)).map(|(i, _e)| i).collect();
+    debug_assert_eq!(changed, &[id_1]);
+
+    debug_assert!(entity_list.get_component_any_mut(id_1, TypeId::of::<ComponentB>()).is_none());
+    let only_comp_a: Vec<_> = entity_list.iter::<(ComponentA,)>().map(|(i, _e)| i).collect();
+    debug_assert_eq!(only_comp_a, &[id_1, id_2]);
+}
+
+#[test]
+fn all_component_type_ids() {
+    use std::any::TypeId;
+
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+    // components nobody has are listed as well
+    entity_list.insert(Entity::new((AgeProp { age: 1 },)).with(ComponentB { beta: 1 }));
+
+    debug_assert_eq!(entity_list.all_component_type_ids(), vec![TypeId::of::<ComponentA>(), TypeId::of::<ComponentB>()]);
+    debug_assert_eq!(Entity::component_type_id(2), None);
+}
+
+#[test]
+fn peek_component() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    let id_1 = entity_list.insert(
+        Entity::new((AgeProp { age: 1 },))
+            .with(ComponentA { alpha: 1.5 })
+    );
+
+    debug_assert_eq!(entity_list.peek_component(id_1, |a: &ComponentA| a.alpha * 2.0), Some(3.0));
+
+    // absent component, then absent entity
+    debug_assert_eq!(entity_list.peek_component(id_1, |b: &ComponentB| b.beta), None);
+    entity_list.remove(id_1);
+    debug_assert_eq!(entity_list.peek_component(id_1, |a: &ComponentA| a.alpha), None);
+}
+
+#[test]
+fn mutate_component() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    let id_1 = entity_list.insert(
+        Entity::new((AgeProp { age: 1 },))
+            .with(ComponentA { alpha: 1.0 })
+    );
+
+    let r = entity_list.mutate_component(id_1, |a: &mut ComponentA| {
+        a.alpha += 1.0;
+        a.alpha
+    });
+    debug_assert_eq!(r, Some(2.0));
+    debug_assert_eq!(entity_list.get_component::<ComponentA>(id_1), Some(&ComponentA { alpha: 2.0 }));
+
+    debug_assert_eq!(entity_list.mutate_component(id_1, |b: &mut ComponentB| b.beta += 1), None);
+    debug_assert_eq!(entity_list.iter::<(ComponentB,)>().count(), 0);
+}
+
+
+#[test]
+fn retain() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+    let ids: Vec<EntityId> = (0..6u32)
+        .map(|i| {
+            let e = Entity::new((AgeProp { age: i },)).with(ComponentA { alpha: i as f32 });
+            entity_list.insert(e)
+        })
+        .collect();
+
+    // like Vec::retain, the predicate selects the entities to keep
+    entity_list.retain(|_id, e| e.age.age % 2 == 1);
+    debug_assert_eq!(entity_list.len(), 3);
+    for (i, id) in ids.iter().enumerate() {
+        debug_assert_eq!(entity_list.contains(*id), i % 2 == 1);
+    }
+    let with_a: Vec<u32> = entity_list.iter::<(ComponentA,)>().map(|(_id, e)| e.age.age).collect();
+    debug_assert_eq!(with_a, &[1, 3, 5]);
+
+    entity_list.retain(|_id, _e| true);
+    debug_assert_eq!(entity_list.len(), 3);
+    entity_list.retain(|_id, _e| false);
+    debug_assert_eq!(entity_list.len(), 0);
+    debug_assert_eq!(entity_list.iter::<(ComponentA,)>().count(), 0);
+}
+
+#[test]
+fn remove_where() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+    let ids: Vec<EntityId> = (0..6u32)
+        .map(|i| {
+            let e = Entity::new((AgeProp { age: i },)).with(ComponentA { alpha: i as f32 });
+            entity_list.insert(e)
+        })
+        .collect();
+
+    // the predicate selects the entities to remove
+    entity_list.remove_where(|_id, e| e.age.age % 2 == 0);
+    debug_assert_eq!(entity_list.len(), 3);
+    for (i, id) in ids.iter().enumerate() {
+        debug_assert_eq!(entity_list.contains(*id), i % 2 == 1);
+    }
+    let with_a: Vec<u32> = entity_list.iter::<(ComponentA,)>().map(|(_id, e)| e.age.age).collect();
+    debug_assert_eq!(with_a, &[1, 3, 5]);
+
+    entity_list.remove_where(|_id, _e| false);
+    debug_assert_eq!(entity_list.len(), 3);
+    entity_list.remove_where(|_id, _e| true);
+    debug_assert_eq!(entity_list.len(), 0);
+    debug_assert_eq!(entity_list.iter::<(ComponentA,)>().count(), 0);
+}
+
+#[test]
+fn assert_consistent() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+    entity_list.assert_consistent();
+
+    let mut ids = Vec::new();
+    for i in 0..10 {
+        let e = Entity::new((AgeProp { age: i },)).with(ComponentA { alpha: i as f32 });
+        let e = if i % 2 == 0 { e.with(ComponentB { beta: i as i32 }) } else { e };
+        ids.push(entity_list.insert(e));
+        entity_list.assert_consistent();
+    }
+
+    entity_list.remove(ids[3]);
+    entity_list.assert_consistent();
+
+    entity_list.add_component_for_entity(ids[1], ComponentB { beta: 1 });
+    entity_list.assert_consistent();
+    entity_list.remove_component_for_entity::<ComponentA>(ids[2]);
+    entity_list.assert_consistent();
+
+    entity_list.retain(|_id, e| e.age.age < 7);
+    entity_list.assert_consistent();
+    entity_list.remove_where(|_id, e| e.age.age == 5);
+    entity_list.assert_consistent();
+    let remaining: Vec<_> = entity_list.iter_all().map(|(id, _e)| id).collect();
+    debug_assert_eq!(remaining, &[ids[0], ids[1], ids[2], ids[4], ids[6]]);
+    let with_b: Vec<_> = entity_list.iter::<(ComponentB,)>().map(|(id, _e)| id).collect();
+    debug_assert_eq!(with_b, &[ids[0], ids[1], ids[2], ids[4], ids[6]]);
+}
+
+#[test]
+#[should_panic]
+fn assert_consistent_out_of_sync() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+    let id = entity_list.insert(Entity::new((AgeProp { age: 0 },)));
+    // adding a component behind the back of the list leaves its bitset out of date
+    entity_list.get_mut(id).unwrap().add(ComponentA { alpha: 0.0 });
+    entity_list.assert_consistent();
+}
+
+#[test]
+fn remove_component_where() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    let mut ids = Vec::new();
+    for i in 0..10 {
+        let e = Entity::new((AgeProp { age: i },)).with(ComponentB { beta: i as i32 });
+        let e = if i < 5 { e.with(ComponentA { alpha: i as f32 }) } else { e };
+        ids.push(entity_list.insert(e));
+    }
+    ids.push(entity_list.insert(Entity::new((AgeProp { age: 10 },))));
+
+    debug_assert_eq!(entity_list.remove_component_where(|b: &ComponentB| b.beta >= 7), 3);
+    let with_b: Vec<_> = entity_list.iter::<(ComponentB,)>().map(|(id, _e)| id).collect();
+    debug_assert_eq!(with_b, &ids[0..7]);
+    // the entities themselves are kept
+    debug_assert_eq!(entity_list.len(), 11);
+
+    debug_assert_eq!(entity_list.remove_component_where(|a: &ComponentA| a.alpha < 2.0), 2);
+    let with_a_and_b: Vec<_> = entity_list.iter::<(ComponentA, ComponentB)>().map(|(id, _e)| id).collect();
+    debug_assert_eq!(with_a_and_b, &ids[2..5]);
+
+    debug_assert_eq!(entity_list.remove_component_where(|_b: &ComponentB| false), 0);
+}
+
+#[test]
+fn drain_component() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    let mut ids = Vec::new();
+    for i in 0..6 {
+        let e = Entity::new((AgeProp { age: i },)).with(ComponentA { alpha: i as f32 });
+        let e = if i % 2 == 1 { e.with(ComponentB { beta: i as i32 }) } else { e };
+        ids.push(entity_list.insert(e));
+    }
+
+    let drained = entity_list.drain_component::<ComponentB>();
+    let expected: Vec<(EntityId, Box<ComponentB>)> = vec![
+        (ids[1], Box::new(ComponentB { beta: 1 })),
+        (ids[3], Box::new(ComponentB { beta: 3 })),
+        (ids[5], Box::new(ComponentB { beta: 5 })),
+    ];
+    debug_assert_eq!(drained, expected);
+    debug_assert!(entity_list.iter_all().all(|(_id, e)| !e.has::<ComponentB>()));
+    debug_assert_eq!(entity_list.iter::<(ComponentB,)>().count(), 0);
+    // the entities and their other components are kept
+    debug_assert_eq!(entity_list.iter::<(ComponentA,)>().count(), 6);
+    entity_list.assert_consistent();
+
+    debug_assert!(entity_list.drain_component::<ComponentB>().is_empty());
+}
+
+#[test]
+fn bitsets_exist_from_the_start() {
+    // every component is queryable right away, for an empty list as well.
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+    debug_assert_eq!(entity_list.iter::<(ComponentA,)>().count(), 0);
+    debug_assert_eq!(entity_list.iter::<(ComponentA, ComponentB)>().count(), 0);
+
+    let id_a = entity_list.insert(Entity::new((AgeProp { age: 0 },)).with(ComponentA { alpha: 1.0 }));
+    let id_b = entity_list.insert(Entity::new((AgeProp { age: 1 },)).with(ComponentB { beta: 1 }));
+    let with_a: Vec<_> = entity_list.iter::<(ComponentA,)>().map(|(i, _e)| i).collect();
+    debug_assert_eq!(with_a, &[id_a]);
+    let with_b: Vec<_> = entity_list.iter::<(ComponentB,)>().map(|(i, _e)| i).collect();
+    debug_assert_eq!(with_b, &[id_b]);
+
+    entity_list.add_component_for_entity(id_a, ComponentB { beta: 2 });
+    debug_assert_eq!(entity_list.iter::<(ComponentA, ComponentB)>().count(), 1);
+    entity_list.remove_component_for_entity::<ComponentA>(id_a);
+    debug_assert_eq!(entity_list.iter::<(ComponentA,)>().count(), 0);
+    debug_assert_eq!(entity_list.iter::<(ComponentB,)>().count(), 2);
+    entity_list.assert_consistent();
+}
+
+#[test]
+fn with_config() {
+    let small: EntityList<Entity> = EntityList::with_config(EntityListConfig::default().with_bitset_capacity(64).with_arena_capacity(1));
+    let mut big: EntityList<Entity> = EntityList::with_config(EntityListConfig::default().with_bitset_capacity(100_000).with_arena_capacity(500));
+    debug_assert!(small.bitset_memory_usage() < big.bitset_memory_usage());
+    debug_assert!(big.memory_report().arena_capacity_bytes >= 500 * std::mem::size_of::<Entity>());
+    debug_assert_eq!(big.memory_report().entity_bytes, 0);
+
+    // inserting does not reallocate the arena up to the configured capacity
+    let arena_bytes = big.memory_report().arena_capacity_bytes;
+    for i in 0..500 {
+        big.insert(Entity::new((AgeProp { age: i },)).with(ComponentA { alpha: 0.0 }));
+    }
+    debug_assert_eq!(big.memory_report().arena_capacity_bytes, arena_bytes);
+
+    // the small list grows as needed
+    let mut small = small;
+    for i in 0..500 {
+        let e = Entity::new((AgeProp { age: i },));
+        small.insert(if i % 2 == 0 { e.with(ComponentB { beta: 0 }) } else { e });
+    }
+    debug_assert_eq!(small.iter::<(ComponentB,)>().count(), 250);
+    debug_assert_eq!(big.iter::<(ComponentA,)>().count(), 500);
+    small.assert_consistent();
+
+    // oversized capacities are clamped rather than rejected
+    let huge: EntityList<Entity> = EntityList::with_config(EntityListConfig::default().with_bitset_capacity(u32::MAX));
+    debug_assert_eq!(huge.len(), 0);
+}
+
+#[test]
+fn lazy_bitsets() {
+    let mut eager: EntityList<Entity> = EntityList::new();
+    let mut lazy: EntityList<Entity> = EntityList::with_config(EntityListConfig::default().with_lazy_bitsets(true));
+    debug_assert_eq!(lazy.bitset_memory_usage(), 0);
+    debug_assert!(eager.bitset_memory_usage() > 0);
+    debug_assert_eq!(lazy.iter::<(ComponentA,)>().count(), 0);
+
+    // only the bitset of A gets created
+    for list in [&mut eager, &mut lazy].iter_mut() {
+        for i in 0..10 {
+            list.insert(Entity::new((AgeProp { age: i },)).with(ComponentA { alpha: i as f32 }));
+        }
+    }
+    debug_assert!(lazy.bitset_memory_usage() < eager.bitset_memory_usage());
+    debug_assert_eq!(lazy.iter::<(ComponentB,)>().count(), 0);
+    debug_assert_eq!(lazy.iter::<(ComponentA, ComponentB)>().count(), 0);
+
+    // both behave the same way from there
+    for list in [&mut eager, &mut lazy].iter_mut() {
+        let ids: Vec<EntityId> = list.iter::<(ComponentA,)>().map(|(i, _e)| i).collect();
+        list.add_component_for_entity(ids[3], ComponentB { beta: 3 });
+        list.get_mut(ids[5]).unwrap().b = Some(Box::new(ComponentB { beta: 5 }));
+        list.refresh(ids[5]);
+        list.remove_component_for_entity::<ComponentA>(ids[5]);
+        list.remove(ids[0]);
+        list.retain(|_id, e| e.age.age != 9);
+        list.assert_consistent();
+    }
+    for list in [&eager, &lazy].iter() {
+        let with_a: Vec<u32> = list.iter::<(ComponentA,)>().map(|(_i, e)| e.age.age).collect();
+        debug_assert_eq!(with_a, &[1, 2, 3, 4, 6, 7, 8]);
+        let with_b: Vec<u32> = list.iter::<(ComponentB,)>().map(|(_i, e)| e.age.age).collect();
+        debug_assert_eq!(with_b, &[3, 5]);
+        debug_assert_eq!(list.iter::<(ComponentA, ComponentB)>().count(), 1);
+    }
+
+    // the config is kept by clones
+    let mut cloned = lazy.clone();
+    cloned.insert(Entity::new((AgeProp { age: 10 },)).with(ComponentB { beta: 10 }));
+    debug_assert_eq!(cloned.iter::<(ComponentB,)>().count(), 3);
+}
+
+#[test]
+fn lazy_bitsets_never_used_component() {
+    let mut entity_list: EntityList<Entity> = EntityList::with_config(EntityListConfig::default().with_lazy_bitsets(true));
+    for i in 0..5 {
+        entity_list.insert(Entity::new((AgeProp { age: i },)).with(ComponentA { alpha: 0.0 }));
+    }
+    entity_list.track_changes::<ComponentB>();
+
+    // no entity ever had B, so it has no bitset, but every query still works
+    debug_assert_eq!(entity_list.iter::<(ComponentB,)>().count(), 0);
+    debug_assert_eq!(entity_list.iter_mut::<(ComponentB,)>().count(), 0);
+    debug_assert_eq!(entity_list.iter::<(ComponentB, ComponentA)>().count(), 0);
+    debug_assert_eq!(entity_list.iter_range::<(ComponentB,)>(0, 5).count(), 0);
+    debug_assert_eq!(entity_list.iter_filter(|_b: &ComponentB| true).count(), 0);
+    debug_assert_eq!(entity_list.iter_mut_scan::<(ComponentB,)>().count(), 0);
+    debug_assert_eq!(entity_list.iter_changed::<ComponentB>(entity_list.current_tick()).count(), 0);
+    debug_assert!(entity_list.nth_matching::<(ComponentB,)>(0).is_none());
+    debug_assert!(entity_list.page::<(ComponentB,)>(0, 10).is_empty());
+    debug_assert_eq!(entity_list.fold_query::<(ComponentB,), _>(0, |acc, _id, _e| acc + 1), 0);
+    debug_assert!(entity_list.collect_component::<ComponentB>().is_empty());
+    debug_assert_eq!(entity_list.as_readonly().iter::<(ComponentB,)>().count(), 0);
+    entity_list.for_each_component_mut(|_id, _b: &mut ComponentB| unreachable!());
+    debug_assert_eq!(entity_list.remove_component_where(|_b: &ComponentB| true), 0);
+    debug_assert_eq!(entity_list.remove_query::<(ComponentB,)>(), 0);
+    debug_assert_eq!(entity_list.stats().count_of::<ComponentB>(), 0);
+    debug_assert!(entity_list.stats().component_counts.contains_key("b"));
+
+    // refreshing B doesn't create its bitset for nothing
+    let memory = entity_list.bitset_memory_usage();
+    entity_list.refresh_component::<ComponentB>();
+    debug_assert_eq!(entity_list.bitset_memory_usage(), memory);
+
+    // neither do updates of B which don't apply to any entity
+    let stale = entity_list.insert(Entity::new((AgeProp { age: 5 },)));
+    entity_list.remove(stale);
+    entity_list.apply_component_updates::<ComponentB>(Vec::new());
+    entity_list.apply_component_updates(vec![(stale, ComponentB { beta: 5 })]);
+    debug_assert_eq!(entity_list.bitset_memory_usage(), memory);
+
+    // lists derived from this one keep lazy bitsets
+    let cloned = entity_list.clone_entities_only();
+    let mut eager: EntityList<Entity> = EntityList::new();
+    for i in 0..5 {
+        eager.insert(Entity::new((AgeProp { age: i },)).with(ComponentA { alpha: 0.0 }));
+    }
+    debug_assert!(cloned.bitset_memory_usage() < eager.clone_entities_only().bitset_memory_usage());
+    debug_assert_eq!(cloned.iter::<(ComponentB,)>().count(), 0);
+    debug_assert_eq!(cloned.iter::<(ComponentA,)>().count(), 5);
+    entity_list.assert_consistent();
+}
+
+#[test]
+fn query_after_bitset_removal() {
+    let mut entity_list: EntityList<Entity> = EntityList::with_config(EntityListConfig::default().with_lazy_bitsets(true));
+    let id = entity_list.insert(Entity::new((AgeProp { age: 0 },)).with(ComponentB { beta: 0 }));
+    let memory = entity_list.bitset_memory_usage();
+    debug_assert_eq!(entity_list.iter::<(ComponentB,)>().count(), 1);
+
+    // B is removed out-of-band, so refreshing it drops its (lazy) bitset altogether
+    entity_list.get_mut(id).unwrap().b = None;
+    entity_list.refresh_component::<ComponentB>();
+    debug_assert!(entity_list.bitset_memory_usage() < memory);
+    debug_assert_eq!(entity_list.iter::<(ComponentB,)>().count(), 0);
+    debug_assert_eq!(entity_list.iter_mut::<(ComponentB,)>().count(), 0);
+    debug_assert!(entity_list.nth_matching::<(ComponentB,)>(0).is_none());
+
+    // and it comes back with the next entity having B
+    entity_list.add_component_for_entity(id, ComponentB { beta: 1 });
+    debug_assert_eq!(entity_list.iter::<(ComponentB,)>().count(), 1);
+    entity_list.assert_consistent();
+}
+
+#[test]
+fn ensure_bitset() {
+    use generational_arena::Arena;
+
+    let mut arena: Arena<Entity> = Arena::new();
+    for i in 0..6 {
+        let e = Entity::new((AgeProp { age: i },));
+        arena.insert(if i % 2 == 0 { e.with(ComponentB { beta: i as i32 }) } else { e });
+    }
+    let mut entity_list = EntityList::from_arena(arena);
+    // bitsets of a list built from an arena already exist, so this does nothing
+    entity_list.ensure_bitset::<ComponentB>();
+    let ages: Vec<_> = entity_list.iter::<(ComponentB,)>().map(|(_i, e)| e.age.age).collect();
+    debug_assert_eq!(ages, &[0, 2, 4]);
+
+    let mut lazy: EntityList<Entity> = EntityList::with_config(EntityListConfig::default().with_lazy_bitsets(true));
+    let ids: Vec<EntityId> = (0..6).map(|i| lazy.insert(Entity::new((AgeProp { age: i },)))).collect();
+    debug_assert_eq!(lazy.bitset_memory_usage(), 0);
+    lazy.ensure_bitset::<ComponentA>();
+    debug_assert!(lazy.bitset_memory_usage() > 0);
+    debug_assert_eq!(lazy.iter::<(ComponentA,)>().count(), 0);
+
+    // the bitset is populated from the entities having the component already
+    lazy.get_mut(ids[1]).unwrap().b = Some(Box::new(ComponentB { beta: 1 }));
+    lazy.get_mut(ids[4]).unwrap().b = Some(Box::new(ComponentB { beta: 4 }));
+    lazy.ensure_bitset::<ComponentB>();
+    let ages: Vec<_> = lazy.iter::<(ComponentB,)>().map(|(_i, e)| e.age.age).collect();
+    debug_assert_eq!(ages, &[1, 4]);
+    lazy.add_component_for_entity(ids[2], ComponentA { alpha: 2.0 });
+    debug_assert_eq!(lazy.iter::<(ComponentA,)>().count(), 1);
+    lazy.assert_consistent();
+}
+
+mod tiny {
+    use mobec::define_entity;
+
+    #[derive(Debug, PartialEq, Clone, Copy, Default)]
+    pub struct Marker;
+
+    define_entity! {
+        #[derive(Debug)]
+        pub struct Tiny {
+            props => {},
+            components => {
+                #[marker] marker => Marker,
+            }
+        }
+    }
+}
+
+#[test]
+#[ignore = "fills a list up to MAX_ENTITY_SLOTS, which takes about 400MB"]
+fn try_insert_full_list() {
+    use mobec::MAX_ENTITY_SLOTS;
+    use tiny::{Marker, Tiny};
+
+    let mut entity_list: EntityList<Tiny> = EntityList::new();
+    for _ in 0..MAX_ENTITY_SLOTS {
+        entity_list.insert(Tiny::new(()).with(Marker));
+    }
+    // the arena never grew past the bitsets, so every entity can be queried
+    debug_assert_eq!(entity_list.iter::<(Marker,)>().count(), MAX_ENTITY_SLOTS);
+
+    // the list is full, whatever the budget
+    debug_assert!(entity_list.try_insert(Tiny::new(()).with(Marker), usize::MAX).is_err());
+    debug_assert_eq!(entity_list.len(), MAX_ENTITY_SLOTS);
+
+    let (id, _e) = entity_list.iter_all().next().unwrap();
+    entity_list.remove(id);
+    debug_assert!(entity_list.try_insert(Tiny::new(()).with(Marker), usize::MAX).is_ok());
+    debug_assert_eq!(entity_list.iter::<(Marker,)>().count(), MAX_ENTITY_SLOTS);
+}
\ No newline at end of file
@@ -0,0 +1,227 @@
+use mobec::{
+    define_entity,
+    EntityList,
+    EntityBase,
+};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Health(f32);
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Mana(f32);
+
+define_entity! {
+    #[derive(Debug)]
+    pub struct Entity {
+        props => {},
+        components => {
+            health => Health,
+            mana => Mana,
+        }
+    }
+}
+
+#[test]
+/// Tests that two components wrapping the same type are kept apart.
+fn newtype_components() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    let id_1 = entity_list.insert(
+        Entity::new(())
+            .with(Health(10.0))
+    );
+    let id_2 = entity_list.insert(
+        Entity::new(())
+            .with(Mana(5.0))
+    );
+    let id_3 = entity_list.insert(
+        Entity::new(())
+            .with(Health(1.0))
+            .with(Mana(2.0))
+    );
+
+    let with_health: Vec<_> = entity_list.iter::<(Health,)>().map(|(i, _e)| i).collect();
+    let with_mana: Vec<_> = entity_list.iter::<(Mana,)>().map(|(i, _e)| i).collect();
+    debug_assert_eq!(with_health, &[id_1, id_3]);
+    debug_assert_eq!(with_mana, &[id_2, id_3]);
+
+    let e = entity_list.get(id_3).unwrap();
+    debug_assert_eq!(e.get::<Health>(), Some(&Health(1.0)));
+    debug_assert_eq!(e.get::<Mana>(), Some(&Mana(2.0)));
+}
+
+
+mod derived_clone {
+    use mobec::define_entity;
+
+    define_entity! {
+        #[derive(Debug, Clone)]
+        pub struct Entity {
+            props => {},
+            components => {
+                health => super::Health,
+            }
+        }
+    }
+}
+
+#[test]
+/// Tests that `Clone` may be listed in the derives alongside the generated impl.
+fn derived_clone() {
+    let e = derived_clone::Entity::new(()).with(Health(3.0));
+    let cloned = e.clone();
+    debug_assert_eq!(cloned.get::<Health>(), Some(&Health(3.0)));
+}
+
+mod no_clone {
+    use mobec::define_entity;
+
+    /// Not `Clone` on purpose.
+    #[derive(Debug, PartialEq)]
+    pub struct Handle(pub u32);
+
+    define_entity! {
+        #[derive(Debug)]
+        #[no_clone]
+        pub struct Entity {
+            props => {
+                name: String,
+            },
+            components => {
+                handle => Handle,
+            }
+        }
+    }
+}
+
+#[test]
+/// Tests that an entity with a non-Clone component compiles with `#[no_clone]`.
+fn no_clone() {
+    let mut entity_list: EntityList<no_clone::Entity> = EntityList::new();
+    let id = entity_list.insert(
+        no_clone::Entity::new((String::from("file"),))
+            .with(no_clone::Handle(7))
+    );
+    let e = entity_list.get(id).unwrap();
+    debug_assert_eq!(e.name, "file");
+    debug_assert_eq!(e.get::<no_clone::Handle>(), Some(&no_clone::Handle(7)));
+}
+
+mod marker {
+    use mobec::define_entity;
+
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    pub struct Dead;
+
+    define_entity! {
+        #[derive(Debug)]
+        pub struct Entity {
+            props => {},
+            components => {
+                health => super::Health,
+                #[marker] dead => Dead,
+            }
+        }
+    }
+}
+
+#[test]
+/// Tests that marker components are tracked by the bitsets without being boxed.
+fn marker_components() {
+    use marker::Dead;
+
+    let mut entity_list: EntityList<marker::Entity> = EntityList::new();
+    let id_1 = entity_list.insert(
+        marker::Entity::new(())
+            .with(Health(1.0))
+            .with(Dead)
+    );
+    let id_2 = entity_list.insert(
+        marker::Entity::new(())
+            .with(Health(2.0))
+    );
+
+    let e = entity_list.get(id_1).unwrap();
+    debug_assert_eq!(std::mem::size_of_val(&e.dead), 1);
+    debug_assert_eq!(e.get::<Dead>(), Some(&Dead));
+
+    let dead: Vec<_> = entity_list.iter::<(Dead,)>().map(|(i, _e)| i).collect();
+    debug_assert_eq!(dead, &[id_1]);
+
+    entity_list.add_component_for_entity(id_2, Dead);
+    debug_assert_eq!(entity_list.remove_component_for_entity::<Dead>(id_1), Some(Box::new(Dead)));
+    let dead: Vec<_> = entity_list.iter::<(Health, Dead)>().map(|(i, _e)| i).collect();
+    debug_assert_eq!(dead, &[id_2]);
+}
+
+mod many_components {
+    use mobec::define_entity;
+
+    macro_rules! components {
+        ($($name:ident),*) => {
+            $(
+                #[derive(Debug, PartialEq, Clone, Copy)]
+                pub struct $name(pub u32);
+            )*
+        }
+    }
+
+    components!(C0, C1, C2, C3, C4, C5, C6, C7, C8, C9, C10, C11, C12, C13, C14, C15, C16, C17, C18, C19);
+
+    define_entity! {
+        #[derive(Debug)]
+        pub struct Entity {
+            props => {},
+            components => {
+                c0 => C0, c1 => C1, c2 => C2, c3 => C3, c4 => C4,
+                c5 => C5, c6 => C6, c7 => C7, c8 => C8, c9 => C9,
+                c10 => C10, c11 => C11, c12 => C12, c13 => C13, c14 => C14,
+                c15 => C15, c16 => C16, c17 => C17, c18 => C18, c19 => C19,
+            }
+        }
+    }
+}
+
+#[test]
+/// Tests that the 16 components limit only applies to queries, not to entities.
+fn many_components() {
+    use many_components::*;
+
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+    let id_1 = entity_list.insert(
+        Entity::new(())
+            .with(C0(0))
+            .with(C17(17))
+            .with(C19(19))
+    );
+    let _id_2 = entity_list.insert(
+        Entity::new(())
+            .with(C0(0))
+            .with(C19(19))
+    );
+
+    let ids: Vec<_> = entity_list.iter::<(C0, C17, C19)>().map(|(i, _e)| i).collect();
+    debug_assert_eq!(ids, &[id_1]);
+    debug_assert_eq!(entity_list.get(id_1).unwrap().get::<C17>(), Some(&C17(17)));
+}
+
+#[test]
+/// Tests that queries may ask for more than 16 components at once.
+fn many_components_query() {
+    use many_components::*;
+
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+    let all = Entity::new(())
+        .with(C0(0)).with(C1(1)).with(C2(2)).with(C3(3)).with(C4(4))
+        .with(C5(5)).with(C6(6)).with(C7(7)).with(C8(8)).with(C9(9))
+        .with(C10(10)).with(C11(11)).with(C12(12)).with(C13(13)).with(C14(14))
+        .with(C15(15)).with(C16(16)).with(C17(17));
+    let id_1 = entity_list.insert(all.clone());
+    entity_list.insert(all.clone().with_removed::<C9>());
+    let id_3 = entity_list.insert(all.with(C18(18)));
+
+    let ids: Vec<_> = entity_list.iter::<(
+        C0, C1, C2, C3, C4, C5, C6, C7, C8, C9, C10, C11, C12, C13, C14, C15, C16, C17
+    )>().map(|(i, _e)| i).collect();
+    debug_assert_eq!(ids, &[id_1, id_3]);
+}
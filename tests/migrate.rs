@@ -0,0 +1,96 @@
+use mobec::{
+    define_entity,
+    EntityList,
+    EntityBase,
+};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Position {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Health(u32);
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Name(&'static str);
+
+mod v1 {
+    use super::*;
+
+    define_entity! {
+        #[derive(Debug)]
+        pub struct Entity {
+            props => {
+                name: Name,
+            },
+            components => {
+                position => Position,
+                health => Health,
+            }
+        }
+    }
+}
+
+/// `Position` became `Location`, a tuple instead of 2 fields.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Location(f32, f32);
+
+mod v2 {
+    use super::*;
+
+    define_entity! {
+        #[derive(Debug)]
+        pub struct Entity {
+            props => {
+                name: Name,
+            },
+            components => {
+                location => Location,
+                health => Health,
+            }
+        }
+    }
+}
+
+#[test]
+fn migrate() {
+    let mut entity_list: EntityList<v1::Entity> = EntityList::new();
+    let id_1 = entity_list.insert(
+        v1::Entity::new((Name("a"),))
+            .with(Position { x: 1.0, y: 2.0 })
+            .with(Health(10))
+    );
+    let id_2 = entity_list.insert(v1::Entity::new((Name("b"),)).with(Health(5)));
+    let id_3 = entity_list.insert(v1::Entity::new((Name("c"),)).with(Position { x: 3.0, y: 4.0 }));
+    entity_list.remove(id_2);
+    let id_4 = entity_list.insert(v1::Entity::new((Name("d"),)).with(Health(7)));
+
+    let (migrated, id_map): (EntityList<v2::Entity>, _) = entity_list.migrate(|e| {
+        let mut new_e = v2::Entity::new((e.name,));
+        if let Some(position) = e.get::<Position>() {
+            new_e.add(Location(position.x, position.y));
+        }
+        if let Some(health) = e.get::<Health>() {
+            new_e.add(*health);
+        }
+        new_e
+    });
+
+    // every old id maps to the new id of its entity
+    debug_assert_eq!(migrated.len(), 3);
+    debug_assert_eq!(id_map.iter().map(|(old_id, _new_id)| *old_id).collect::<Vec<_>>(), &[id_1, id_4, id_3]);
+    let new_id = |old_id| id_map.iter().find(|(id, _new_id)| *id == old_id).map(|(_id, new_id)| *new_id).unwrap();
+    let (id_1, id_3, id_4) = (new_id(id_1), new_id(id_3), new_id(id_4));
+    debug_assert_eq!(migrated.get(id_1).map(|e| e.name), Some(Name("a")));
+    debug_assert_eq!(migrated.get(id_3).map(|e| e.name), Some(Name("c")));
+    debug_assert_eq!(migrated.get(id_4).map(|e| e.name), Some(Name("d")));
+
+    // bitsets are built for the new components
+    let with_location: Vec<_> = migrated.iter::<(Location,)>().map(|(id, e)| (id, *e.get::<Location>().unwrap())).collect();
+    debug_assert_eq!(with_location, &[(id_1, Location(1.0, 2.0)), (id_3, Location(3.0, 4.0))]);
+    let with_health: Vec<_> = migrated.iter::<(Health,)>().map(|(id, _e)| id).collect();
+    debug_assert_eq!(with_health, &[id_1, id_4]);
+    migrated.assert_consistent();
+}
\ No newline at end of file
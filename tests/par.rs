@@ -0,0 +1,102 @@
+#![cfg(feature = "rayon")]
+
+use rayon::prelude::*;
+use mobec::{
+    define_entity,
+    EntityList,
+    EntityBase,
+};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Position(i64, i64);
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Visible;
+
+define_entity! {
+    #[derive(Debug)]
+    pub struct Entity {
+        props => {
+            weight: i64,
+        },
+        components => {
+            position => Position,
+            visible => Visible,
+        }
+    }
+}
+
+#[test]
+fn par_iter() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+    for i in 0..5000i64 {
+        let mut e = Entity::new((i,));
+        if i % 3 != 0 {
+            e = e.with(Position(i, -i));
+        }
+        if i % 5 == 0 {
+            e = e.with(Visible);
+        }
+        entity_list.insert(e);
+    }
+    // leave some holes in the bitsets.
+    entity_list.retain(|_id, e| e.weight % 7 == 0);
+
+    let serial: i64 = entity_list.iter::<(Position, Visible)>()
+        .map(|(_id, e)| e.weight + e.get::<Position>().unwrap().1)
+        .sum();
+    let parallel: i64 = entity_list.par_iter::<(Position, Visible)>()
+        .map(|(_id, e)| e.weight + e.get::<Position>().unwrap().1)
+        .sum();
+    debug_assert_eq!(serial, parallel);
+
+    let mut serial_ids: Vec<_> = entity_list.iter::<(Position,)>().map(|(id, _)| id).collect();
+    let mut parallel_ids: Vec<_> = entity_list.par_iter::<(Position,)>().map(|(id, _)| id).collect();
+    serial_ids.sort();
+    parallel_ids.sort();
+    debug_assert_eq!(serial_ids, parallel_ids);
+    debug_assert!(entity_list.par_iter::<(Position,)>().all(|(id, e)| entity_list.get(id).is_some() && e.has::<Position>()));
+
+    // the empty query matches every entity, but none of the free slots nor the pooled entities
+    let pooled = serial_ids[0];
+    entity_list.despawn_to_pool(pooled);
+    let mut serial_ids: Vec<_> = entity_list.iter_all().map(|(id, _)| id).collect();
+    let mut parallel_ids: Vec<_> = entity_list.par_iter::<()>().map(|(id, _)| id).collect();
+    serial_ids.sort();
+    parallel_ids.sort();
+    debug_assert_eq!(serial_ids, parallel_ids);
+    debug_assert!(!parallel_ids.contains(&pooled));
+}
+
+#[test]
+fn compute_into() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+    for i in 0..1000i64 {
+        let mut e = Entity::new((i,));
+        if i % 2 == 0 {
+            e = e.with(Position(i, i * 10));
+        }
+        entity_list.insert(e);
+    }
+
+    let mut out = vec![-1i64; 1000];
+    entity_list.compute_into::<(Position,), _>(&mut out, |_id, e| {
+        let position = e.get::<Position>()?;
+        Some((e.weight as usize, position.0 + position.1))
+    });
+    for (i, value) in out.iter().enumerate() {
+        let i = i as i64;
+        if i % 2 == 0 {
+            debug_assert_eq!(*value, i * 11);
+        } else {
+            debug_assert_eq!(*value, -1);
+        }
+    }
+
+    // entities may skip their slot.
+    let mut out = vec![0i64; 1000];
+    entity_list.compute_into::<(Position,), _>(&mut out, |_id, e| {
+        if e.weight < 500 { Some((e.weight as usize, 1)) } else { None }
+    });
+    debug_assert_eq!(out.iter().sum::<i64>(), 250);
+}
\ No newline at end of file
@@ -0,0 +1,163 @@
+use mobec::{
+    define_entity,
+    EntityList,
+    EntityBase,
+};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Speed(f32);
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Damage(u32);
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Position(f32, f32);
+
+define_entity! {
+    #[derive(Debug)]
+    pub struct Bullet {
+        props => {
+            position: Position,
+        },
+        components => {
+            speed => Speed,
+            damage => Damage,
+        }
+    }
+}
+
+#[test]
+fn despawn_to_pool() {
+    let mut entity_list: EntityList<Bullet> = EntityList::new();
+
+    let id_1 = entity_list.insert(Bullet::new((Position(0.0, 0.0),)).with(Speed(1.0)).with(Damage(5)));
+    let id_2 = entity_list.insert(Bullet::new((Position(1.0, 1.0),)).with(Speed(2.0)));
+
+    entity_list.despawn_to_pool(id_1);
+    debug_assert_eq!(entity_list.pool_len(), 1);
+    debug_assert_eq!(entity_list.len(), 1);
+    let e = entity_list.get(id_1).unwrap();
+    debug_assert!(e.get::<Speed>().is_none());
+    debug_assert!(e.get::<Damage>().is_none());
+    debug_assert_eq!(entity_list.iter::<(Speed,)>().map(|(i, _e)| i).collect::<Vec<_>>(), &[id_2]);
+    debug_assert_eq!(entity_list.iter::<(Damage,)>().count(), 0);
+
+    // pooling twice does nothing
+    entity_list.despawn_to_pool(id_1);
+    debug_assert_eq!(entity_list.pool_len(), 1);
+}
+
+#[test]
+fn pooled_entities_are_skipped() {
+    let mut entity_list: EntityList<Bullet> = EntityList::new();
+
+    let ids: Vec<_> = (0..3).map(|i| {
+        entity_list.insert(Bullet::new((Position(i as f32, 0.0),)))
+    }).collect();
+    entity_list.despawn_to_pool(ids[1]);
+
+    debug_assert_eq!(entity_list.iter_all().map(|(i, _e)| i).collect::<Vec<_>>(), &[ids[0], ids[2]]);
+    debug_assert_eq!(entity_list.iter_all_mut().count(), 2);
+    let mut visited = Vec::new();
+    entity_list.for_each_property_mut(|id, _e| visited.push(id));
+    debug_assert_eq!(visited, &[ids[0], ids[2]]);
+    debug_assert_eq!(entity_list.stats().entity_count, 2);
+    debug_assert_eq!(entity_list.memory_report().entity_bytes, 2 * std::mem::size_of::<Bullet>());
+    debug_assert_eq!(entity_list.query().iter().map(|(i, _e)| i).collect::<Vec<_>>(), &[ids[0], ids[2]]);
+    debug_assert_eq!(entity_list.query().without::<Speed>().iter().map(|(i, _e)| i).collect::<Vec<_>>(), &[ids[0], ids[2]]);
+
+    // retain does not see pooled entities, so it can't remove them either
+    entity_list.retain(|_id, _e| false);
+    debug_assert_eq!(entity_list.len(), 0);
+    debug_assert_eq!(entity_list.pool_len(), 1);
+    debug_assert_eq!(entity_list.spawn_from_pool((Position(5.0, 5.0),)), ids[1]);
+    debug_assert_eq!(entity_list.iter_all().map(|(i, _e)| i).collect::<Vec<_>>(), &[ids[1]]);
+}
+
+#[test]
+/// Tests that the empty query, which matches every entity, skips the pooled ones as well.
+fn pooled_entities_are_skipped_by_empty_query() {
+    let mut entity_list: EntityList<Bullet> = EntityList::new();
+
+    let ids: Vec<_> = (0..4).map(|i| {
+        entity_list.insert(Bullet::new((Position(i as f32, 0.0),)))
+    }).collect();
+    entity_list.despawn_to_pool(ids[1]);
+
+    let live = [ids[0], ids[2], ids[3]];
+    debug_assert_eq!(entity_list.iter::<()>().map(|(i, _e)| i).collect::<Vec<_>>(), &live);
+    debug_assert_eq!(entity_list.iter::<()>().remaining(), 3);
+    debug_assert_eq!(entity_list.iter_mut::<()>().map(|(i, _e)| i).collect::<Vec<_>>(), &live);
+    debug_assert_eq!(entity_list.page::<()>(1, 10).iter().map(|(i, _e)| *i).collect::<Vec<_>>(), &live[1..]);
+    debug_assert_eq!(entity_list.nth_matching::<()>(1).map(|(i, _e)| i), Some(ids[2]));
+    debug_assert_eq!(entity_list.iter_range::<()>(0, 4).count(), 3);
+    debug_assert_eq!(entity_list.iter_mut_scan::<()>().count(), 3);
+
+    // the pooled entity survives, and can still be spawned
+    debug_assert_eq!(entity_list.remove_query::<()>(), 3);
+    debug_assert_eq!(entity_list.len(), 0);
+    debug_assert_eq!(entity_list.pool_len(), 1);
+    let id = entity_list.spawn_from_pool((Position(7.0, 7.0),));
+    debug_assert_eq!(id, ids[1]);
+    debug_assert_eq!(entity_list.get(id).unwrap().position, Position(7.0, 7.0));
+    debug_assert_eq!(entity_list.drain_query::<()>().map(|(i, _e)| i).collect::<Vec<_>>(), &[ids[1]]);
+}
+
+#[test]
+fn spawn_from_pool() {
+    let mut entity_list: EntityList<Bullet> = EntityList::new();
+
+    let ids: Vec<_> = (0..3).map(|i| {
+        entity_list.insert(Bullet::new((Position(i as f32, 0.0),)).with(Speed(1.0)))
+    }).collect();
+    entity_list.despawn_to_pool(ids[0]);
+    entity_list.despawn_to_pool(ids[2]);
+
+    // pooled slots are reused, last pooled first, with their properties reset
+    let id = entity_list.spawn_from_pool((Position(10.0, 10.0),));
+    debug_assert_eq!(id, ids[2]);
+    debug_assert_eq!(entity_list.get(id).unwrap().position, Position(10.0, 10.0));
+    entity_list.add_component_for_entity(id, Damage(3));
+    debug_assert_eq!(entity_list.iter::<(Damage,)>().map(|(i, _e)| i).collect::<Vec<_>>(), &[ids[2]]);
+    debug_assert_eq!(entity_list.iter::<(Speed,)>().map(|(i, _e)| i).collect::<Vec<_>>(), &[ids[1]]);
+
+    // removing a pooled entity takes it out of the pool, and an empty pool inserts new entities
+    entity_list.remove(ids[0]);
+    debug_assert_eq!(entity_list.pool_len(), 0);
+    let id = entity_list.spawn_from_pool((Position(20.0, 20.0),));
+    debug_assert!(!ids.contains(&id));
+    debug_assert_eq!(entity_list.pool_len(), 0);
+    debug_assert_eq!(entity_list.len(), 3);
+    debug_assert_eq!(entity_list.get(id).unwrap().position, Position(20.0, 20.0));
+}
+
+#[test]
+/// Tests that pooled entities can't be given components or be replaced until they are spawned again.
+fn pooled_entities_are_rejected() {
+    let mut entity_list: EntityList<Bullet> = EntityList::new();
+
+    let id_1 = entity_list.insert(Bullet::new((Position(0.0, 0.0),)));
+    let id_2 = entity_list.insert(Bullet::new((Position(1.0, 1.0),)).with(Speed(2.0)));
+    entity_list.despawn_to_pool(id_1);
+    debug_assert!(entity_list.is_pooled(id_1));
+    debug_assert!(!entity_list.is_pooled(id_2));
+
+    debug_assert!(entity_list.replace(id_1, Bullet::new((Position(5.0, 5.0),))).is_none());
+    debug_assert!(!entity_list.swap(id_1, id_2));
+    debug_assert_eq!(entity_list.add_component_for_entity(id_1, Damage(3)), Some(Damage(3)));
+    entity_list.apply_component_updates(vec![(id_1, Speed(4.0)), (id_2, Speed(3.0))]);
+    debug_assert_eq!(entity_list.get(id_1).unwrap().position, Position(0.0, 0.0));
+    debug_assert_eq!(entity_list.iter::<(Damage,)>().count(), 0);
+    debug_assert_eq!(entity_list.iter::<(Speed,)>().map(|(i, e)| (i, *e.get::<Speed>().unwrap())).collect::<Vec<_>>(), &[(id_2, Speed(3.0))]);
+
+    // get_or_insert_with inserts a new entity instead of handing out the pooled one
+    let (id_3, _e) = entity_list.get_or_insert_with(Some(id_1), || Bullet::new((Position(2.0, 2.0),)));
+    debug_assert_ne!(id_3, id_1);
+    debug_assert_eq!(entity_list.pool_len(), 1);
+
+    let id = entity_list.spawn_from_pool((Position(7.0, 7.0),));
+    debug_assert_eq!(id, id_1);
+    debug_assert!(!entity_list.is_pooled(id));
+    debug_assert!(entity_list.add_component_for_entity(id, Damage(3)).is_none());
+    debug_assert_eq!(entity_list.iter::<(Damage,)>().map(|(i, _e)| i).collect::<Vec<_>>(), &[id_1]);
+}
@@ -0,0 +1,99 @@
+use mobec::{
+    define_entity,
+    EntityList,
+    EntityBase,
+    EntityId,
+};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ComponentA {
+    alpha: f32,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ComponentB {
+    beta: i32,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ComponentC {
+    ceta: u32,
+}
+
+define_entity! {
+    #[derive(Debug)]
+    pub struct Entity {
+        props => {},
+        components => {
+            a => ComponentA,
+            b => ComponentB,
+            c => ComponentC,
+        }
+    }
+}
+
+fn generate_list() -> EntityList<Entity> {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    for i in 0..60 {
+        let e = Entity::new(());
+        let e = if i % 2 == 0 { e.with(ComponentA { alpha: i as f32 }) } else { e };
+        let e = if i % 3 == 0 { e.with(ComponentB { beta: i }) } else { e };
+        let e = if i % 5 == 0 { e.with(ComponentC { ceta: i as u32 }) } else { e };
+        entity_list.insert(e);
+    }
+    // leave a hole, which must never be yielded
+    let id = entity_list.insert(Entity::new(()));
+    entity_list.remove(id);
+
+    entity_list
+}
+
+fn ids<'a>(iter: impl Iterator<Item=(EntityId, &'a Entity)>) -> Vec<EntityId> {
+    iter.map(|(i, _e)| i).collect()
+}
+
+#[test]
+fn query_with() {
+    let entity_list = generate_list();
+
+    debug_assert_eq!(ids(entity_list.query().iter()), ids(entity_list.iter_all()));
+    debug_assert_eq!(
+        ids(entity_list.query().with::<ComponentA>().iter()),
+        ids(entity_list.iter::<(ComponentA,)>())
+    );
+    debug_assert_eq!(
+        ids(entity_list.query().with::<ComponentA>().with::<ComponentB>().iter()),
+        ids(entity_list.iter::<(ComponentA, ComponentB)>())
+    );
+}
+
+#[test]
+fn query_without() {
+    let entity_list = generate_list();
+
+    let expected = ids(entity_list.iter::<(ComponentA,)>().filter(|(_i, e)| !e.has::<ComponentB>()));
+    debug_assert_eq!(expected.len(), 20);
+    debug_assert_eq!(ids(entity_list.query().with::<ComponentA>().without::<ComponentB>().iter()), expected);
+
+    let expected = ids(entity_list.iter_all().filter(|(_i, e)| !e.has::<ComponentC>()));
+    debug_assert_eq!(expected.len(), 48);
+    debug_assert_eq!(ids(entity_list.query().without::<ComponentC>().iter()), expected);
+}
+
+#[test]
+fn query_or_with() {
+    let entity_list = generate_list();
+
+    let expected = ids(entity_list.iter_all().filter(|(_i, e)| {
+        (e.has::<ComponentA>() && !e.has::<ComponentB>()) || e.has::<ComponentC>()
+    }));
+    let query = entity_list.query()
+        .with::<ComponentA>()
+        .without::<ComponentB>()
+        .or_with::<ComponentC>();
+    debug_assert_eq!(ids(query.iter()), expected);
+
+    // "or" with every entity is still every entity
+    debug_assert_eq!(ids(entity_list.query().or_with::<ComponentC>().iter()), ids(entity_list.iter_all()));
+}
\ No newline at end of file
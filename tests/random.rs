@@ -0,0 +1,65 @@
+#![cfg(feature = "rand")]
+
+use rand::{SeedableRng, rngs::StdRng};
+use mobec::{
+    define_entity,
+    EntityList,
+    EntityBase,
+    EntityId,
+};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SpawnPoint;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Enemy;
+
+define_entity! {
+    #[derive(Debug)]
+    pub struct Entity {
+        props => {
+            index: usize,
+        },
+        components => {
+            spawn_point => SpawnPoint,
+            enemy => Enemy,
+        }
+    }
+}
+
+#[test]
+fn random_matching() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+    for i in 0..50 {
+        let e = Entity::new((i,));
+        let e = if i % 10 == 3 { e.with(SpawnPoint) } else { e };
+        entity_list.insert(e);
+    }
+
+    let draw = |seed: u64| -> Vec<EntityId> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..100).map(|_| entity_list.random_matching::<(SpawnPoint,), _>(&mut rng).unwrap().0).collect()
+    };
+    let first = draw(42);
+    debug_assert_eq!(first, draw(42));
+    debug_assert_ne!(first, draw(43));
+
+    let mut picked: Vec<usize> = first.iter().map(|id| entity_list.get(*id).unwrap().index).collect();
+    picked.sort_unstable();
+    picked.dedup();
+    debug_assert_eq!(picked, &[3, 13, 23, 33, 43]);
+
+    let mut rng = StdRng::seed_from_u64(42);
+    debug_assert!(entity_list.random_matching::<(Enemy,), _>(&mut rng).is_none());
+
+    // the empty query picks among the live entities only, the pooled ones excepted
+    entity_list.retain(|_id, e| e.index >= 45);
+    let pooled: Vec<EntityId> = entity_list.iter_all().map(|(id, _e)| id).take(3).collect();
+    for id in &pooled {
+        entity_list.despawn_to_pool(*id);
+    }
+    for _ in 0..100 {
+        let (id, _e) = entity_list.random_matching::<(), _>(&mut rng).unwrap();
+        debug_assert!(!pooled.contains(&id));
+    }
+}
\ No newline at end of file
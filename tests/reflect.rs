@@ -0,0 +1,52 @@
+#![cfg(feature = "reflect")]
+
+use std::any::TypeId;
+
+use bevy_reflect::{Reflect, Struct};
+use mobec::{
+    define_entity,
+    EntityList,
+    EntityBase,
+};
+
+#[derive(Debug, PartialEq, Clone, Copy, Reflect)]
+pub struct Position {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Reflect)]
+pub struct Health {
+    hp: u32,
+}
+
+define_entity! {
+    #[derive(Debug)]
+    #[reflect]
+    pub struct Entity {
+        props => {},
+        components => {
+            position => Position,
+            health => Health,
+        }
+    }
+}
+
+#[test]
+fn reflect_component() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+    let id = entity_list.insert(Entity::new(()).with(Position { x: 1.0, y: 2.0 }));
+
+    let position = entity_list.reflect_component(id, TypeId::of::<Position>()).expect("entity has a Position");
+    let position: &dyn Struct = match position.reflect_ref() {
+        bevy_reflect::ReflectRef::Struct(s) => s,
+        _ => panic!("Position is a struct"),
+    };
+    debug_assert_eq!(position.field_len(), 2);
+    debug_assert_eq!(position.name_at(1), Some("y"));
+    debug_assert_eq!(position.field("y").and_then(|y| y.downcast_ref::<f32>()), Some(&2.0));
+
+    debug_assert!(entity_list.reflect_component(id, TypeId::of::<Health>()).is_none());
+    entity_list.remove(id);
+    debug_assert!(entity_list.reflect_component(id, TypeId::of::<Position>()).is_none());
+}
\ No newline at end of file
@@ -80,4 +80,44 @@ fn deserialized_have_same_values() {
     debug_assert_eq!(only_comp_b, &[id_2, id_4]);
 
     debug_assert_eq!(comp_a_and_b, &[id_4]);
+}
+
+#[test]
+/// Tests that ids serialized outside of the list still resolve against the deserialized list.
+fn external_ids_round_trip() {
+    use std::collections::HashMap;
+    use mobec::EntityId;
+
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+    let mut names: HashMap<EntityId, String> = HashMap::new();
+    for i in 0..5 {
+        let id = entity_list.insert(
+            Entity::new((CommonProp,))
+                .with(ComponentB { beta: i })
+        );
+        names.insert(id, format!("entity {}", i));
+    }
+    // leave a stale id in the map, and reuse its slot with a newer generation
+    let stale_id = *names.iter().find(|(_id, name)| *name == "entity 2").unwrap().0;
+    entity_list.remove(stale_id);
+    let reused_id = entity_list.insert(Entity::new((CommonProp,)).with(ComponentA { alpha: 1.0 }));
+    names.insert(reused_id, String::from("reused"));
+
+    let bytes = bincode::serialize(&(&entity_list, &names)).expect("EntityList and ids should be serializable");
+    let (mut deserialized_entity_list, deserialized_names): (EntityList<Entity>, HashMap<EntityId, String>) =
+        bincode::deserialize(&bytes).expect("EntityList and ids should be deserializable");
+
+    debug_assert_eq!(deserialized_names, names);
+    for (id, name) in &deserialized_names {
+        if *id == stale_id {
+            debug_assert!(deserialized_entity_list.get(*id).is_none());
+        } else {
+            debug_assert_eq!(deserialized_entity_list.get(*id), entity_list.get(*id), "{}", name);
+            debug_assert!(deserialized_entity_list.get(*id).is_some());
+        }
+    }
+
+    // new ids handed out after the round-trip never alias the stale one
+    let new_id = deserialized_entity_list.insert(Entity::new((CommonProp,)));
+    debug_assert!(!deserialized_names.contains_key(&new_id));
 }
\ No newline at end of file
@@ -0,0 +1,100 @@
+use hashbrown::HashMap;
+
+use mobec::{
+    define_entity,
+    EntityList,
+    EntityBase,
+    EntityId,
+    SpatialIndex,
+};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Position {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Speed {
+    x: f32,
+    y: f32,
+}
+
+define_entity! {
+    #[derive(Debug)]
+    pub struct Entity {
+        props => {},
+        components => {
+            position => Position,
+            speed => Speed,
+        }
+    }
+}
+
+/// A trivial uniform grid, with cells of 10x10.
+#[derive(Default)]
+struct Grid {
+    cells: HashMap<(i32, i32), Vec<EntityId>>,
+}
+
+impl Grid {
+    fn cell(x: f32, y: f32) -> (i32, i32) {
+        ((x / 10.0).floor() as i32, (y / 10.0).floor() as i32)
+    }
+
+    fn at(&self, x: f32, y: f32) -> &[EntityId] {
+        self.cells.get(&Self::cell(x, y)).map(|v| v.as_slice()).unwrap_or(&[])
+    }
+}
+
+impl SpatialIndex<Entity> for Grid {
+    fn clear(&mut self) {
+        self.cells.clear();
+    }
+
+    fn insert(&mut self, id: EntityId, entity: &Entity) {
+        if let Some(position) = entity.get::<Position>() {
+            self.cells.entry(Self::cell(position.x, position.y)).or_default().push(id);
+        }
+    }
+}
+
+fn generate_list() -> (EntityList<Entity>, Vec<EntityId>) {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+    let ids = vec![
+        entity_list.insert(Entity::new(()).with(Position { x: 1.0, y: 1.0 })),
+        entity_list.insert(Entity::new(()).with(Position { x: 5.0, y: 9.0 }).with(Speed { x: 1.0, y: 0.0 })),
+        entity_list.insert(Entity::new(()).with(Position { x: 15.0, y: 1.0 })),
+        entity_list.insert(Entity::new(()).with(Speed { x: 1.0, y: 0.0 })),
+        entity_list.insert(Entity::new(()).with(Position { x: -3.0, y: 25.0 })),
+    ];
+    (entity_list, ids)
+}
+
+#[test]
+fn rebuild_spatial() {
+    let (mut entity_list, ids) = generate_list();
+    let mut grid = Grid::default();
+
+    entity_list.rebuild_spatial(&mut grid);
+    debug_assert_eq!(grid.at(0.0, 0.0), &[ids[0], ids[1]]);
+    debug_assert_eq!(grid.at(10.0, 0.0), &[ids[2]]);
+    debug_assert_eq!(grid.at(-10.0, 20.0), &[ids[4]]);
+    debug_assert_eq!(grid.cells.values().map(|v| v.len()).sum::<usize>(), 4);
+
+    // rebuilding starts from scratch
+    entity_list.remove(ids[0]);
+    entity_list.rebuild_spatial(&mut grid);
+    debug_assert_eq!(grid.at(0.0, 0.0), &[ids[1]]);
+}
+
+#[test]
+fn rebuild_spatial_with() {
+    let (entity_list, ids) = generate_list();
+    let mut grid = Grid::default();
+
+    // only the moving entities
+    entity_list.rebuild_spatial_with::<(Position, Speed), _>(&mut grid);
+    debug_assert_eq!(grid.at(0.0, 0.0), &[ids[1]]);
+    debug_assert_eq!(grid.cells.values().map(|v| v.len()).sum::<usize>(), 1);
+}
\ No newline at end of file
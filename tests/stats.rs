@@ -0,0 +1,173 @@
+use mobec::{
+    define_entity,
+    EntityList,
+    EntityBase,
+};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ComponentA {
+    alpha: f32,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ComponentB {
+    beta: i32,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ComponentC {
+    ceta: u32,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct CommonProp;
+
+define_entity! {
+    #[derive(Debug)]
+    pub struct Entity {
+        props => {
+            common: CommonProp,
+        },
+        components => {
+            a => ComponentA,
+            b => ComponentB,
+            c => ComponentC,
+        }
+    }
+}
+
+fn generate_list() -> EntityList<Entity> {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+
+    for i in 0..10 {
+        entity_list.insert(
+            Entity::new((CommonProp,))
+                .with(ComponentA { alpha: i as f32 })
+        );
+    }
+    for i in 0..5 {
+        entity_list.insert(
+            Entity::new((CommonProp,))
+                .with(ComponentA { alpha: i as f32 })
+                .with(ComponentB { beta: i })
+        );
+    }
+    for _ in 0..3 {
+        entity_list.insert(Entity::new((CommonProp,)));
+    }
+    entity_list
+}
+
+#[test]
+fn stats() {
+    let mut entity_list = generate_list();
+    let stats = entity_list.stats();
+
+    debug_assert_eq!(stats.entity_count, 18);
+    debug_assert_eq!(stats.count_of::<ComponentA>(), 15);
+    debug_assert_eq!(stats.count_of::<ComponentB>(), 5);
+    debug_assert_eq!(stats.count_of::<ComponentC>(), 0);
+    // not a component
+    debug_assert_eq!(stats.count_of::<CommonProp>(), 0);
+    debug_assert_eq!(stats.component_counts.get("a"), Some(&15));
+    // {A}, {A, B} and {}
+    debug_assert_eq!(stats.distinct_component_masks, 3);
+
+    let ids: Vec<_> = entity_list.iter::<(ComponentB,)>().map(|(i, _e)| i).collect();
+    for id in ids {
+        entity_list.remove(id);
+    }
+    let stats = entity_list.stats();
+    debug_assert_eq!(stats.entity_count, 13);
+    debug_assert_eq!(stats.count_of::<ComponentA>(), 10);
+    debug_assert_eq!(stats.count_of::<ComponentB>(), 0);
+    debug_assert_eq!(stats.distinct_component_masks, 2);
+}
+
+#[test]
+fn component_count_histogram() {
+    let mut entity_list = generate_list();
+    entity_list.insert(
+        Entity::new((CommonProp,))
+            .with(ComponentA { alpha: 1.0 })
+            .with(ComponentB { beta: 1 })
+            .with(ComponentC { ceta: 1 })
+    );
+
+    debug_assert_eq!(entity_list.component_count_histogram(), vec![3, 10, 5, 1]);
+
+    let empty: EntityList<Entity> = EntityList::new();
+    debug_assert_eq!(empty.component_count_histogram(), vec![0, 0, 0, 0]);
+}
+
+#[test]
+fn debug_alternate_form() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+    entity_list.insert(
+        Entity::new((CommonProp,))
+            .with(ComponentA { alpha: 1.0 })
+            .with(ComponentB { beta: 1 })
+    );
+    entity_list.insert(
+        Entity::new((CommonProp,))
+            .with(ComponentA { alpha: 1.0 })
+    );
+
+    let regular = format!("{:?}", entity_list);
+    debug_assert!(!regular.contains("bitsets"));
+
+    let alternate = format!("{:#?}", entity_list);
+    debug_assert!(alternate.contains("bitsets"));
+    debug_assert!(alternate.contains("len: 2"));
+    debug_assert!(alternate.contains("len: 1"));
+    debug_assert!(alternate.contains("len: 0"));
+    debug_assert!(alternate.contains("capacity: "));
+    debug_assert!(alternate.contains("\"a\": BitSet"));
+}
+
+
+#[test]
+fn bitset_memory_usage() {
+    let mut entity_list = generate_list();
+    let usage = entity_list.bitset_memory_usage();
+    debug_assert!(usage > 0);
+
+    // indices past the first layer0 words force the bitsets to grow.
+    let mut last_id = None;
+    for i in 0..10_000 {
+        last_id = Some(entity_list.insert(
+            Entity::new((CommonProp,))
+                .with(ComponentC { ceta: i })
+        ));
+    }
+    let grown_usage = entity_list.bitset_memory_usage();
+    debug_assert!(grown_usage > usage);
+
+    // tracking changes adds a bitset.
+    entity_list.track_changes::<ComponentC>();
+    entity_list.add_component_for_entity(last_id.unwrap(), ComponentC { ceta: 0 });
+    debug_assert!(entity_list.bitset_memory_usage() > grown_usage);
+}
+
+#[test]
+fn memory_report() {
+    use generational_arena::Arena;
+
+    let entity_size = std::mem::size_of::<Entity>();
+    let mut entity_list: EntityList<Entity> = EntityList::from_arena(Arena::with_capacity(32));
+
+    let report = entity_list.memory_report();
+    debug_assert_eq!(report.entity_bytes, 0);
+    debug_assert_eq!(report.arena_capacity_bytes, 32 * entity_size);
+    debug_assert_eq!(report.bitset_bytes, entity_list.bitset_memory_usage());
+
+    let mut ids = Vec::new();
+    for i in 0..10 {
+        ids.push(entity_list.insert(Entity::new((CommonProp,)).with(ComponentA { alpha: i as f32 })));
+    }
+    entity_list.remove(ids[0]);
+    let report = entity_list.memory_report();
+    debug_assert_eq!(report.entity_bytes, 9 * entity_size);
+    debug_assert_eq!(report.arena_capacity_bytes, 32 * entity_size);
+    debug_assert_eq!(report.bitset_bytes, entity_list.bitset_memory_usage());
+}
\ No newline at end of file
@@ -0,0 +1,108 @@
+use mobec::{
+    define_entity,
+    EntityList,
+    EntityBase,
+};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ComponentA {
+    alpha: f32,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Dead;
+
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Selected;
+
+define_entity! {
+    #[derive(Debug)]
+    pub struct Entity {
+        props => {},
+        components => {
+            a => ComponentA,
+            #[marker] dead => Dead,
+            #[marker] selected => Selected,
+        }
+    }
+}
+
+fn generate_list() -> (EntityList<Entity>, Vec<mobec::EntityId>) {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+    let ids = (0..4)
+        .map(|i| entity_list.insert(Entity::new(()).with(ComponentA { alpha: i as f32 })))
+        .collect();
+    (entity_list, ids)
+}
+
+#[test]
+fn tag() {
+    let (mut entity_list, ids) = generate_list();
+
+    entity_list.tag::<Dead>(ids[1]);
+    entity_list.tag::<Dead>(ids[3]);
+    entity_list.tag::<Selected>(ids[3]);
+
+    let dead: Vec<_> = entity_list.iter::<(Dead,)>().map(|(i, _e)| i).collect();
+    debug_assert_eq!(dead, &[ids[1], ids[3]]);
+    let dead_and_selected: Vec<_> = entity_list.iter::<(Dead, Selected)>().map(|(i, _e)| i).collect();
+    debug_assert_eq!(dead_and_selected, &[ids[3]]);
+
+    // tagging twice doesn't change anything
+    entity_list.tag::<Dead>(ids[1]);
+    debug_assert_eq!(entity_list.iter::<(Dead,)>().count(), 2);
+
+    // tagging a removed entity is a no-op
+    entity_list.remove(ids[0]);
+    entity_list.tag::<Dead>(ids[0]);
+    debug_assert_eq!(entity_list.iter::<(Dead,)>().count(), 2);
+}
+
+#[test]
+fn untag() {
+    let (mut entity_list, ids) = generate_list();
+
+    entity_list.tag::<Dead>(ids[1]);
+    entity_list.tag::<Dead>(ids[2]);
+    entity_list.untag::<Dead>(ids[1]);
+    // untagging an entity without the tag is a no-op
+    entity_list.untag::<Dead>(ids[0]);
+
+    let dead: Vec<_> = entity_list.iter::<(Dead,)>().map(|(i, _e)| i).collect();
+    debug_assert_eq!(dead, &[ids[2]]);
+    debug_assert!(!entity_list.get(ids[1]).unwrap().has::<Dead>());
+}
+
+#[test]
+fn is_tagged() {
+    let (mut entity_list, ids) = generate_list();
+
+    entity_list.tag::<Selected>(ids[2]);
+    debug_assert!(entity_list.is_tagged::<Selected>(ids[2]));
+    debug_assert!(!entity_list.is_tagged::<Selected>(ids[1]));
+    debug_assert!(!entity_list.is_tagged::<Dead>(ids[2]));
+
+    entity_list.remove(ids[2]);
+    debug_assert!(!entity_list.is_tagged::<Selected>(ids[2]));
+}
+
+#[test]
+fn take_tagged() {
+    let (mut entity_list, ids) = generate_list();
+
+    entity_list.tag::<Selected>(ids[0]);
+    entity_list.tag::<Selected>(ids[2]);
+    entity_list.tag::<Selected>(ids[3]);
+    entity_list.tag::<Dead>(ids[3]);
+
+    let selected = entity_list.take_tagged::<Selected>();
+    debug_assert_eq!(selected, &[ids[0], ids[2], ids[3]]);
+
+    debug_assert_eq!(entity_list.iter::<(Selected,)>().count(), 0);
+    debug_assert!(ids.iter().all(|id| !entity_list.is_tagged::<Selected>(*id)));
+    // the other components are untouched
+    debug_assert!(entity_list.is_tagged::<Dead>(ids[3]));
+    debug_assert_eq!(entity_list.len(), 4);
+
+    debug_assert!(entity_list.take_tagged::<Selected>().is_empty());
+}
\ No newline at end of file
@@ -0,0 +1,100 @@
+use rayon::prelude::*;
+use mobec::{
+    define_entity,
+    EntityList,
+    EntityBase,
+    EntityId,
+    ReadOnlyView,
+};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Health(u32);
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Armor(u32);
+
+define_entity! {
+    #[derive(Debug)]
+    pub struct Entity {
+        props => {
+            level: u32,
+        },
+        components => {
+            health => Health,
+            armor => Armor,
+        }
+    }
+}
+
+#[test]
+fn readonly_view_is_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<ReadOnlyView<'static, Entity>>();
+}
+
+#[test]
+fn parallel_reads() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+    let ids: Vec<EntityId> = (0..1000).map(|i| {
+        let e = Entity::new((i,)).with(Health(i * 2));
+        let e = if i % 4 == 0 { e.with(Armor(1)) } else { e };
+        entity_list.insert(e)
+    }).collect();
+
+    let view = entity_list.as_readonly();
+    let total_health: u32 = ids.par_iter()
+        .filter_map(|id| view.get_component::<Health>(*id))
+        .map(|health| health.0)
+        .sum();
+    debug_assert_eq!(total_health, (0..1000).map(|i| i * 2).sum());
+
+    let (armored, levels) = rayon::join(
+        || view.iter::<(Armor,)>().count(),
+        || ids.par_iter().map(|id| view.get(*id).map_or(0, |e| e.level)).max(),
+    );
+    debug_assert_eq!(armored, 250);
+    debug_assert_eq!(levels, Some(999));
+
+    rayon::scope(|s| {
+        for chunk in ids.chunks(100) {
+            s.spawn(move |_| {
+                debug_assert!(chunk.iter().all(|id| view.contains(*id)));
+            });
+        }
+    });
+    debug_assert_eq!(view.len(), 1000);
+}
+
+#[test]
+fn iter_mut_with_rest() {
+    let mut entity_list: EntityList<Entity> = EntityList::new();
+    let ids: Vec<EntityId> = (0..10)
+        .map(|i| entity_list.insert(Entity::new((i,)).with(Health(i * 10))))
+        .collect();
+    // leave holes, so that lookups have to tell stale ids apart
+    entity_list.remove(ids[3]);
+    entity_list.remove(ids[7]);
+    let armored = entity_list.insert(Entity::new((100,)).with(Armor(1)));
+
+    // every entity with health takes the health of the next entity, if it still exists
+    entity_list.iter_mut_with_rest::<(Health,)>(|id, e, rest| {
+        debug_assert!(!rest.contains(id));
+        debug_assert!(rest.get(ids[3]).is_none());
+        debug_assert_eq!(rest.len(), 8);
+        debug_assert_eq!(rest.get_component::<Armor>(armored), Some(&Armor(1)));
+        let next = ids.get(e.level as usize + 1).and_then(|next| rest.get_component::<Health>(*next));
+        if let Some(next) = next {
+            e.health = Some(Box::new(*next));
+        }
+    });
+
+    let healths: Vec<_> = entity_list.iter::<(Health,)>().map(|(_i, e)| (e.level, e.get::<Health>().unwrap().0)).collect();
+    // entities are visited in order, so the next entity has not been updated yet when it is read
+    debug_assert_eq!(healths, &[(0, 10), (1, 20), (2, 20), (4, 50), (5, 60), (6, 60), (8, 90), (9, 90)]);
+
+    // the rest view sees every other entity, in order
+    entity_list.iter_mut_with_rest::<(Armor,)>(|_id, e, rest| {
+        e.level = rest.iter().map(|(_i, other)| other.level).sum();
+    });
+    debug_assert_eq!(entity_list.get(armored).unwrap().level, 35);
+}
\ No newline at end of file